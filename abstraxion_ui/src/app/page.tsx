@@ -12,6 +12,7 @@ import ChessBoard from "@/components/ChessBoard";
 import { useUserProfileUnified } from "@/hooks/useUserProfileUnified";
 import { useChessGame } from "@/hooks/useChessGame";
 import { useGlobalStats } from "@/hooks/useGlobalStats";
+import { describeGameState, gameWinner, isGameActive } from "@/types/chess";
 import type { ChessMove } from "@/types/chess";
 
 export default function Page(): JSX.Element {
@@ -22,11 +23,11 @@ export default function Page(): JSX.Element {
 
   const {
     userProfile,
-    allUsers,
+    leaderboard,
     loading: userLoading,
     error: userError,
     initializeUser,
-    refreshAllUsers,
+    refreshLeaderboard,
   } = useUserProfileUnified();
 
   const {
@@ -36,10 +37,14 @@ export default function Page(): JSX.Element {
     error: gameError,
     moveError,
     timeStatus,
+    incomingChallenges,
     createGame,
+    acceptChallenge,
+    declineChallenge,
     makeMove,
     fetchGame,
     resignGame,
+    claimTimeout,
     selectGame,
     refreshUserGames,
     proposeDraw,
@@ -53,6 +58,7 @@ export default function Page(): JSX.Element {
   const [opponentAddress, setOpponentAddress] = useState("");
   const [showNewGameForm, setShowNewGameForm] = useState(false);
   const [moveInput, setMoveInput] = useState("");
+  const [challengeSent, setChallengeSent] = useState(false);
 
   const handleLogin = async () => {
     if (!account?.bech32Address) {
@@ -79,6 +85,7 @@ export default function Page(): JSX.Element {
       if (result) {
         setShowNewGameForm(false);
         setOpponentAddress("");
+        setChallengeSent(true);
       }
     }
   };
@@ -120,9 +127,9 @@ export default function Page(): JSX.Element {
   // Auto-load leaderboard when component mounts
   React.useEffect(() => {
     if (queryClient) {
-      refreshAllUsers();
+      refreshLeaderboard();
     }
-  }, [queryClient, refreshAllUsers]);
+  }, [queryClient, refreshLeaderboard]);
 
   return (
     <main className="min-h-screen bg-gray-900 text-white">
@@ -210,8 +217,48 @@ export default function Page(): JSX.Element {
                 structure="base"
                 disabled={!opponentAddress || gameLoading}
               >
-                Create Game
+                Challenge
               </Button>
+              {challengeSent && (
+                <div className="text-xs text-gray-400 mt-2">Challenge sent, the game starts once your opponent accepts</div>
+              )}
+            </div>
+          )}
+
+          {/* Incoming Challenges */}
+          {incomingChallenges.length > 0 && (
+            <div className="bg-gray-800 p-4 rounded-lg">
+              <h3 className="font-bold mb-2">Challenges ({incomingChallenges.length})</h3>
+              <div className="space-y-2 max-h-40 overflow-y-auto">
+                {incomingChallenges.map((challenge) => (
+                  <div key={challenge.id} className="p-2 rounded bg-gray-700 text-sm">
+                    <div className="truncate">from {challenge.challenger.slice(0, 10) + '...'}</div>
+                    <div className="text-xs text-gray-400">
+                      {challenge.rated ? 'Rated' : 'Casual'}, challenger plays {challenge.challenger_color}
+                    </div>
+                    <div className="flex gap-2 mt-2">
+                      <Button
+                        onClick={() => acceptChallenge(challenge.id)}
+                        structure="base"
+                        disabled={gameLoading}
+                        className="flex-1"
+                      >
+                        Accept
+                      </Button>
+                      {challenge.opponent && (
+                        <Button
+                          onClick={() => declineChallenge(challenge.id)}
+                          structure="base"
+                          disabled={gameLoading}
+                          className="flex-1"
+                        >
+                          Decline
+                        </Button>
+                      )}
+                    </div>
+                  </div>
+                ))}
+              </div>
             </div>
           )}
 
@@ -247,25 +294,25 @@ export default function Page(): JSX.Element {
             <div className="flex justify-between items-center mb-2">
               <h3 className="font-bold">Leaderboard</h3>
               <button
-                onClick={refreshAllUsers}
+                onClick={refreshLeaderboard}
                 className="text-xs px-2 py-1 bg-gray-700 rounded hover:bg-gray-600"
               >
                 🔄
               </button>
             </div>
             <div className="space-y-1 max-h-40 overflow-y-auto">
-              {allUsers.slice(0, 10).map((user, index) => (
-                <div key={user.username} className="flex justify-between items-center text-sm py-1">
+              {leaderboard.map((user) => (
+                <div key={user.address} className="flex justify-between items-center text-sm py-1">
                   <div className="flex items-center gap-2 flex-1 min-w-0">
                     <span className={`font-bold ${
-                      index === 0 ? 'text-yellow-400' : 'text-gray-400'
+                      user.rank === 1 ? 'text-yellow-400' : 'text-gray-400'
                     }`}>
-                      {index + 1}
+                      {user.rank}
                     </span>
                     <span className="truncate">{user.username}</span>
                   </div>
                   <div className="flex items-center gap-2">
-                    <span className="text-yellow-400 font-bold">{user.elo}</span>
+                    <span className="text-yellow-400 font-bold">{user.rating}</span>
                     <button
                       onClick={() => copyToClipboard(user.address)}
                       className={`p-1 rounded transition-colors ${
@@ -297,7 +344,7 @@ export default function Page(): JSX.Element {
             <>
               {/* Game Header */}
               <div className="flex justify-between items-center bg-gray-800 p-3 rounded-lg">
-                <h2 className="font-bold">Game: {currentGame.id.slice(0, 12)}...</h2>
+                <h2 className="font-bold">Game #{currentGame.id}</h2>
                 <button
                   onClick={() => fetchGame(currentGame.id)}
                   className="px-3 py-1 bg-gray-700 rounded hover:bg-gray-600"
@@ -307,7 +354,7 @@ export default function Page(): JSX.Element {
                 </button>
               </div>
 
-              {currentGame.status === 'active' ? (
+              {isGameActive(currentGame.status) ? (
                 <>
                   {/* Compact Time Display */}
                   <div className="bg-gray-800 p-2 rounded-lg flex justify-between items-center">
@@ -316,14 +363,14 @@ export default function Page(): JSX.Element {
                       <span className={`font-mono text-lg ${
                         currentGame.current_turn === 'black' ? 'text-yellow-400' : ''
                       }`}>
-                        {timeStatus ? formatTime(timeStatus.black_time_remaining) : '--:--'}
+                        {timeStatus ? formatTime(timeStatus.black_seconds_remaining) : '--:--'}
                       </span>
                     </div>
                     <div className="flex items-center gap-2">
                       <span className={`font-mono text-lg ${
                         currentGame.current_turn === 'white' ? 'text-yellow-400' : ''
                       }`}>
-                        {timeStatus ? formatTime(timeStatus.white_time_remaining) : '--:--'}
+                        {timeStatus ? formatTime(timeStatus.white_seconds_remaining) : '--:--'}
                       </span>
                       <span className="text-sm">⚪ White</span>
                     </div>
@@ -362,7 +409,7 @@ export default function Page(): JSX.Element {
                     {/* Status */}
                     <div className="flex justify-between text-sm">
                       <span>Turn: <span className="text-yellow-400">{currentGame.current_turn}</span></span>
-                      <span>Moves: {currentGame.moves.split(',').filter(Boolean).length}</span>
+                      <span>Moves: {currentGame.move_count}</span>
                     </div>
 
                     {/* Draw/Resign Buttons */}
@@ -404,6 +451,18 @@ export default function Page(): JSX.Element {
                         >
                           Resign
                         </Button>
+                        {timeStatus?.time_expired && timeStatus.current_player !== (
+                          currentGame.white === account?.bech32Address ? 'white' : 'black'
+                        ) && (
+                          <Button
+                            onClick={() => claimTimeout(currentGame.id)}
+                            structure="base"
+                            disabled={gameLoading}
+                            className="flex-1"
+                          >
+                            Claim Timeout
+                          </Button>
+                        )}
                       </div>
                     )}
                   </div>
@@ -414,12 +473,11 @@ export default function Page(): JSX.Element {
                   <h3 className="text-2xl font-bold mb-4">Game Over!</h3>
                   <div className="text-lg">
                     Result: <span className={`font-bold ${
-                      currentGame.status === 'draw' ? 'text-yellow-400' :
-                      (currentGame.status === 'white_won' && currentGame.white === account?.bech32Address) ||
-                      (currentGame.status === 'black_won' && currentGame.black === account?.bech32Address) 
+                      gameWinner(currentGame.status) === null ? 'text-yellow-400' :
+                      currentGame[gameWinner(currentGame.status)!] === account?.bech32Address
                         ? 'text-green-400' : 'text-red-400'
                     }`}>
-                      {currentGame.status.replace('_', ' ').toUpperCase()}
+                      {describeGameState(currentGame.status)}
                     </span>
                   </div>
                 </div>
@@ -443,9 +501,8 @@ export default function Page(): JSX.Element {
 }
 
 // Helper function for time formatting
-function formatTime(blocks: number): string {
-  if (blocks <= 0) return "0:00";
-  const totalSeconds = blocks;
+function formatTime(totalSeconds: number): string {
+  if (totalSeconds <= 0) return "0:00";
   const hours = Math.floor(totalSeconds / 3600);
   const minutes = Math.floor((totalSeconds % 3600) / 60);
   const seconds = totalSeconds % 60;
@@ -29,37 +29,12 @@ export default function ChessBoard({ game, currentPlayer, onMove, disabled, move
         setPreviousFen(game.current_fen); // Store for rollback
       } catch (e) {
         console.error("Invalid FEN:", game.current_fen);
-        // Fallback to replaying moves
-        if (game?.moves) {
-          chess.reset();
-          const moves = game.moves.split(',').filter(Boolean);
-          moves.forEach(move => {
-            try {
-              chess.move(move);
-            } catch (e) {
-              console.error("Invalid move:", move);
-            }
-          });
-          setPreviousFen(chess.fen());
-        }
       }
-    } else if (game?.moves) {
-      // Fallback: replay moves if no FEN available
-      chess.reset();
-      const moves = game.moves.split(',').filter(Boolean);
-      moves.forEach(move => {
-        try {
-          chess.move(move);
-        } catch (e) {
-          console.error("Invalid move:", move);
-        }
-      });
-      setPreviousFen(chess.fen());
     }
     updateBoard();
     setTurn(chess.turn());
     setPendingMove(null); // Clear any pending moves when game updates
-  }, [game?.current_fen, game?.moves]);
+  }, [game?.current_fen, game?.move_count]);
 
   // Handle move errors by rolling back
   useEffect(() => {
@@ -10,11 +10,10 @@ interface TimeDisplayProps {
 export function TimeDisplay({ timeStatus, playerColor, isCurrentPlayer }: TimeDisplayProps) {
   if (!timeStatus) return null;
 
-  // Convert blocks to human readable time with live countdown
-  const blocksToTime = (blocks: number): string => {
-    if (blocks <= 0) return "0:00";
+  // Convert seconds to human readable time
+  const secondsToTime = (totalSeconds: number): string => {
+    if (totalSeconds <= 0) return "0:00";
     
-    const totalSeconds = blocks; // 1 block = ~1 second
     const days = Math.floor(totalSeconds / 86400);
     const hours = Math.floor((totalSeconds % 86400) / 3600);
     const minutes = Math.floor((totalSeconds % 3600) / 60);
@@ -29,41 +28,38 @@ export function TimeDisplay({ timeStatus, playerColor, isCurrentPlayer }: TimeDi
     }
   };
 
-  // Get live time remaining (accounting for time since last move)
-  const getLiveTimeRemaining = (): number => {
-    if (!timeStatus || timeStatus.move_count < 2) return timeRemaining;
-    
-    // If it's this player's turn, subtract time since last move
-    if (isCurrentPlayer) {
-      return Math.max(0, timeRemaining - timeStatus.time_since_last_move);
-    }
-    
-    return timeRemaining;
-  };
+  // Durations of the time control are in the game's clock unit
+  const formatDuration = (duration: number): string =>
+    timeStatus.clock_unit === 'seconds' ? secondsToTime(duration) : `${duration} blocks`;
 
-  const timeRemaining = playerColor === 'white' 
-    ? timeStatus.white_time_remaining 
-    : timeStatus.black_time_remaining;
+  // The contract already counts the running clock down
+  const liveTimeRemaining = playerColor === 'white' 
+    ? timeStatus.white_seconds_remaining 
+    : timeStatus.black_seconds_remaining;
 
-  const liveTimeRemaining = getLiveTimeRemaining();
   const isLowTime = liveTimeRemaining < 600; // Less than 10 minutes
   const isCriticalTime = liveTimeRemaining < 60; // Less than 1 minute
 
   // Determine increment text
   const getIncrementText = () => {
-    if (timeStatus.move_count < 2) {
+    const timeControl = timeStatus.time_control;
+    if (timeControl === 'unlimited') {
+      return "No clock";
+    } else if (timeStatus.move_count < 2) {
       return "Time starts after both players' first move";
-    } else if (timeStatus.move_count <= 20) {
-      return "+10 min/move";
+    } else if ('fischer' in timeControl) {
+      return `+${formatDuration(timeControl.fischer.increment)}/move`;
+    } else if ('bronstein' in timeControl) {
+      return `${formatDuration(timeControl.bronstein.delay)} delay/move`;
     } else {
-      return "+1 min/move";
+      return `${formatDuration(timeControl.correspondence.per_move)} per move`;
     }
   };
 
   return (
     <div className={`time-display ${isCurrentPlayer ? 'active' : ''}`}>
       <div className={`time-value ${isCriticalTime ? 'critical' : isLowTime ? 'low' : ''}`}>
-        {blocksToTime(liveTimeRemaining)}
+        {secondsToTime(liveTimeRemaining)}
       </div>
       <div className="time-info">
         <span className="player-label">{playerColor === 'white' ? '⚪' : '⚫'} {playerColor}</span>
@@ -0,0 +1,41 @@
+import type { CosmWasmClient } from "@cosmjs/cosmwasm-stargate";
+import type { ChessGame, LeaderboardCursor, LeaderboardEntry } from "../types/chess";
+
+type Querier = Pick<CosmWasmClient, "queryContractSmart">;
+
+// Largest page the contract returns
+export const PAGE_LIMIT = 30;
+
+// Every game of a `list_games` or `get_player_games` listing. Pages can come back short when a
+// filter skips games, only a null `next_start_after` marks the end.
+export async function fetchAllGamePages(
+  queryClient: Querier,
+  contractAddress: string,
+  listing: (startAfter: number | null) => Record<string, unknown>,
+): Promise<ChessGame[]> {
+  const games: ChessGame[] = [];
+  let startAfter: number | null = null;
+  do {
+    const response = await queryClient.queryContractSmart(contractAddress, listing(startAfter));
+    games.push(...(response.games as ChessGame[]));
+    startAfter = response.next_start_after ?? null;
+  } while (startAfter !== null);
+  return games;
+}
+
+// Every leaderboard entry, highest rating first, provisional players included
+export async function fetchFullLeaderboard(
+  queryClient: Querier,
+  contractAddress: string,
+): Promise<LeaderboardEntry[]> {
+  const entries: LeaderboardEntry[] = [];
+  let startAfter: LeaderboardCursor | null = null;
+  do {
+    const response = await queryClient.queryContractSmart(contractAddress, {
+      leaderboard: { start_after: startAfter, limit: PAGE_LIMIT, include_provisional: true },
+    });
+    entries.push(...(response.entries as LeaderboardEntry[]));
+    startAfter = response.next_start_after ?? null;
+  } while (startAfter !== null);
+  return entries;
+}
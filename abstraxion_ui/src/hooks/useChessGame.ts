@@ -5,34 +5,50 @@ import {
   useAbstraxionSigningClient,
   useAbstraxionClient,
 } from "@burnt-labs/abstraxion";
-import type { ChessGame, ChessMove, TimeStatus } from "../types/chess";
-// Browser-compatible hash function
+import { describeGameState, isGameActive } from "../types/chess";
+import { fetchAllGamePages, PAGE_LIMIT } from "./contractListings";
+import type { Challenge, ChessGame, ChessMove, DrawClaim, TimeStatus } from "../types/chess";
 
 const CHESS_CONTRACT_ADDRESS = process.env.NEXT_PUBLIC_CHESS_GAME_ADDRESS || "";
 
+// Clock of new games, ~2 days in blocks plus 10 minutes per move
+const DEFAULT_TIME_CONTROL = { fischer: { initial: 172800, increment: 600 } };
+
+// Value of an event attribute emitted by the contract
+function findAttribute(result: ExecuteResult, key: string): string | undefined {
+  for (const event of result.events) {
+    const attribute = event.attributes.find(attr => attr.key === key);
+    if (attribute) return attribute.value;
+  }
+  return undefined;
+}
+
 interface UseChessGameReturn {
   currentGame: ChessGame | null;
-  userActiveGames: { id: string; game: ChessGame }[];
+  userActiveGames: { id: number; game: ChessGame }[];
   loading: boolean;
   error: string | null;
   moveError: string | null;
   timeStatus: TimeStatus | null;
-  createGame: (opponentAddress: string) => Promise<{ game: ChessGame; gameId: string } | null>;
-  fetchGame: (gameId: string) => Promise<ChessGame | null>;
-  makeMove: (gameId: string, move: ChessMove) => Promise<boolean>;
-  claimVictory: (gameId: string) => Promise<ExecuteResult | null>;
-  acceptDefeat: (gameId: string) => Promise<ExecuteResult | null>;
-  disputeGame: (gameId: string) => Promise<ExecuteResult | null>;
-  resignGame: (gameId: string) => Promise<ExecuteResult | null>;
-  fetchAllGames: () => Promise<{ id: string; game: ChessGame }[]>;
-  fetchActiveGames: () => Promise<{ id: string; game: ChessGame }[]>;
-  fetchUserGames: () => Promise<{ id: string; game: ChessGame }[]>;
-  fetchUserActiveGames: () => Promise<{ id: string; game: ChessGame }[]>;
-  selectGame: (gameId: string) => void;
+  incomingChallenges: Challenge[];
+  createGame: (opponentAddress: string) => Promise<{ challengeId: number } | null>;
+  fetchIncomingChallenges: () => Promise<Challenge[]>;
+  acceptChallenge: (challengeId: number) => Promise<ExecuteResult | null>;
+  declineChallenge: (challengeId: number) => Promise<ExecuteResult | null>;
+  fetchGame: (gameId: number) => Promise<ChessGame | null>;
+  makeMove: (gameId: number, move: ChessMove) => Promise<boolean>;
+  claimTimeout: (gameId: number) => Promise<ExecuteResult | null>;
+  claimDraw: (gameId: number, reason: DrawClaim) => Promise<ExecuteResult | null>;
+  resignGame: (gameId: number) => Promise<ExecuteResult | null>;
+  fetchAllGames: () => Promise<{ id: number; game: ChessGame }[]>;
+  fetchActiveGames: () => Promise<{ id: number; game: ChessGame }[]>;
+  fetchUserGames: () => Promise<{ id: number; game: ChessGame }[]>;
+  fetchUserActiveGames: () => Promise<{ id: number; game: ChessGame }[]>;
+  selectGame: (gameId: number) => void;
   refreshUserGames: () => Promise<void>;
-  checkTimeStatus: (gameId: string) => Promise<TimeStatus | null>;
-  proposeDraw: (gameId: string) => Promise<ExecuteResult | null>;
-  respondToDraw: (gameId: string, accept: boolean) => Promise<ExecuteResult | null>;
+  checkTimeStatus: (gameId: number) => Promise<TimeStatus | null>;
+  proposeDraw: (gameId: number) => Promise<ExecuteResult | null>;
+  respondToDraw: (gameId: number, accept: boolean) => Promise<ExecuteResult | null>;
   clearMoveError: () => void;
 }
 
@@ -42,27 +58,14 @@ export function useChessGame(): UseChessGameReturn {
   const { client: queryClient } = useAbstraxionClient();
   
   const [currentGame, setCurrentGame] = useState<ChessGame | null>(null);
-  const [userActiveGames, setUserActiveGames] = useState<{ id: string; game: ChessGame }[]>([]);
+  const [userActiveGames, setUserActiveGames] = useState<{ id: number; game: ChessGame }[]>([]);
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [moveError, setMoveError] = useState<string | null>(null);
   const [timeStatus, setTimeStatus] = useState<TimeStatus | null>(null);
+  const [incomingChallenges, setIncomingChallenges] = useState<Challenge[]>([]);
 
-  // Generate deterministic game ID from players and block height
-  const generateGameId = useCallback(async (white: string, black: string, blockHeight: number): Promise<string> => {
-    const input = `${white}_${black}_${blockHeight}`;
-    
-    // Use Web Crypto API for browser compatibility
-    const encoder = new TextEncoder();
-    const data = encoder.encode(input);
-    const hashBuffer = await crypto.subtle.digest('SHA-256', data);
-    const hashArray = Array.from(new Uint8Array(hashBuffer));
-    const hashHex = hashArray.map(b => b.toString(16).padStart(2, '0')).join('');
-    
-    return `game_${hashHex.substring(0, 16)}`;
-  }, []);
-
-  const fetchGame = useCallback(async (gameId: string): Promise<ChessGame | null> => {
+  const fetchGame = useCallback(async (gameId: number): Promise<ChessGame | null> => {
     if (!queryClient) {
       setError("Query client not available");
       return null;
@@ -91,7 +94,8 @@ export function useChessGame(): UseChessGameReturn {
     }
   }, [queryClient]);
 
-  const createGame = useCallback(async (opponentAddress: string): Promise<{ game: ChessGame; gameId: string } | null> => {
+  // Games start once the opponent accepts, so this only sends them a challenge
+  const createGame = useCallback(async (opponentAddress: string): Promise<{ challengeId: number } | null> => {
     if (!client || !account) {
       setError("Client or account not available");
       return null;
@@ -107,45 +111,18 @@ export function useChessGame(): UseChessGameReturn {
 
     try {
       // Debug: Log contract address being used
-      console.log("Creating game with chess contract:", CHESS_CONTRACT_ADDRESS);
-
-      // Get current block height for game ID generation
-      const currentHeight = await queryClient?.getHeight();
-      const blockHeight = currentHeight || Date.now();
-      
-      // Determine colors (creator is white by default)
-      const white = account.bech32Address;
-      const black = opponentAddress;
-      
-      const gameId = await generateGameId(white, black, blockHeight);
-
-      const newGame: ChessGame = {
-        id: gameId,
-        white,
-        black,
-        moves: "",
-        current_fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", // Starting position
-        status: 'active',
-        current_turn: 'white',
-        last_move_block: blockHeight,
-        white_time_remaining: 172800, // ~2 days in blocks
-        black_time_remaining: 172800, // ~2 days in blocks
-        created_block: blockHeight,
-        claim_block: null,
-        time_control: "1d",
-        move_count: 0,
-        draw_proposed_by: null
-      };
+      console.log("Creating challenge with chess contract:", CHESS_CONTRACT_ADDRESS);
 
       const msg = { 
-        create_game: { 
-          game_id: gameId,
+        create_challenge: { 
           opponent: opponentAddress,
-          time_control: "1d"
+          color: "white",
+          time_control: DEFAULT_TIME_CONTROL,
+          clock_unit: null,
+          rated: true
         } 
       };
       
-      console.log("Creating game with ID:", gameId);
       const result = await client.execute(
         account.bech32Address,
         CHESS_CONTRACT_ADDRESS,
@@ -153,27 +130,40 @@ export function useChessGame(): UseChessGameReturn {
         "auto"
       );
 
-      if (result.transactionHash) {
-        setCurrentGame(newGame);
-        // Refresh user's active games after creating a game
-        const updatedGames = await fetchUserActiveGames();
-        setUserActiveGames(updatedGames);
-        // Wait for transaction confirmation
-        await new Promise(resolve => setTimeout(resolve, 2000));
-        return { game: newGame, gameId };
+      const challengeId = findAttribute(result, "challenge_id");
+      if (result.transactionHash && challengeId) {
+        console.log("Created challenge with ID:", challengeId);
+        return { challengeId: Number(challengeId) };
       }
 
       return null;
     } catch (err) {
-      console.error("Error creating game:", err);
-      setError(err instanceof Error ? err.message : "Failed to create game");
+      console.error("Error creating challenge:", err);
+      setError(err instanceof Error ? err.message : "Failed to create challenge");
       return null;
     } finally {
       setLoading(false);
     }
-  }, [client, account, queryClient, generateGameId]);
+  }, [client, account]);
+
+  const fetchIncomingChallenges = useCallback(async (): Promise<Challenge[]> => {
+    if (!queryClient || !account?.bech32Address) return [];
+
+    try {
+      const response = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
+        list_open_challenges: { player: account.bech32Address }
+      });
+      const challenges = response.challenges as Challenge[];
+      setIncomingChallenges(challenges);
+      return challenges;
+    } catch (err) {
+      console.error("Error fetching challenges:", err);
+      setError(err instanceof Error ? err.message : "Failed to fetch challenges");
+      return [];
+    }
+  }, [queryClient, account?.bech32Address]);
 
-  const makeMove = useCallback(async (gameId: string, move: ChessMove): Promise<boolean> => {
+  const makeMove = useCallback(async (gameId: number, move: ChessMove): Promise<boolean> => {
     if (!client || !account) {
       setMoveError("Client or account not available");
       return false;
@@ -207,8 +197,8 @@ export function useChessGame(): UseChessGameReturn {
         throw new Error("Not your turn");
       }
 
-      if (game.status !== 'active') {
-        throw new Error(`Game is not active: ${game.status}`);
+      if (!isGameActive(game.status)) {
+        throw new Error(`Game is not active: ${describeGameState(game.status)}`);
       }
 
       // Validate the FEN if provided
@@ -254,8 +244,8 @@ export function useChessGame(): UseChessGameReturn {
     }
   }, [client, account, queryClient, fetchGame]);
 
-  const claimVictory = useCallback(async (gameId: string): Promise<ExecuteResult | null> => {
-    if (!client || !account || !queryClient) {
+  const claimTimeout = useCallback(async (gameId: number): Promise<ExecuteResult | null> => {
+    if (!client || !account) {
       setError("Client not available");
       return null;
     }
@@ -264,25 +254,12 @@ export function useChessGame(): UseChessGameReturn {
     setError(null);
 
     try {
-      const gameResponse = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
-        get_game: { game_id: gameId }
-      });
-
-      if (!gameResponse || !gameResponse.game) {
-        throw new Error("Game not found");
-      }
-
-      const game = gameResponse.game as ChessGame;
-      const currentHeight = await queryClient.getHeight();
-      const blockHeight = currentHeight || Date.now();
-
-      const updatedGame: ChessGame = {
-        ...game,
-        status: 'checkmate_claimed',
-        claim_block: blockHeight
+      const msg = {
+        claim_timeout: {
+          game_id: gameId
+        }
       };
 
-      const msg = { update: { value: JSON.stringify(updatedGame) } };
       const result = await client.execute(
         account.bech32Address,
         CHESS_CONTRACT_ADDRESS,
@@ -291,22 +268,26 @@ export function useChessGame(): UseChessGameReturn {
       );
 
       if (result.transactionHash) {
-        setCurrentGame(updatedGame);
+        // Refresh the game state to show the result
+        const refreshedGame = await fetchGame(gameId);
+        if (refreshedGame) {
+          setCurrentGame(refreshedGame);
+        }
         await new Promise(resolve => setTimeout(resolve, 2000));
       }
 
       return result;
     } catch (err) {
-      console.error("Error claiming victory:", err);
-      setError(err instanceof Error ? err.message : "Failed to claim victory");
+      console.error("Error claiming timeout:", err);
+      setError(err instanceof Error ? err.message : "Failed to claim timeout");
       return null;
     } finally {
       setLoading(false);
     }
-  }, [client, account, queryClient]);
+  }, [client, account, fetchGame]);
 
-  const acceptDefeat = useCallback(async (gameId: string): Promise<ExecuteResult | null> => {
-    if (!client || !account || !queryClient) {
+  const claimDraw = useCallback(async (gameId: number, reason: DrawClaim): Promise<ExecuteResult | null> => {
+    if (!client || !account) {
       setError("Client not available");
       return null;
     }
@@ -315,26 +296,13 @@ export function useChessGame(): UseChessGameReturn {
     setError(null);
 
     try {
-      const gameResponse = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
-        get_game: { game_id: gameId }
-      });
-
-      if (!gameResponse || !gameResponse.game) {
-        throw new Error("Game not found");
-      }
-
-      const game = gameResponse.game as ChessGame;
-      
-      // Determine winner based on who accepted defeat
-      const isWhite = game.white === account.bech32Address;
-      const status = isWhite ? 'black_won' : 'white_won';
-
-      const updatedGame: ChessGame = {
-        ...game,
-        status
+      const msg = {
+        claim_draw: {
+          game_id: gameId,
+          reason
+        }
       };
 
-      const msg = { update: { value: JSON.stringify(updatedGame) } };
       const result = await client.execute(
         account.bech32Address,
         CHESS_CONTRACT_ADDRESS,
@@ -343,22 +311,26 @@ export function useChessGame(): UseChessGameReturn {
       );
 
       if (result.transactionHash) {
-        setCurrentGame(updatedGame);
+        // Refresh the game state to show the result
+        const refreshedGame = await fetchGame(gameId);
+        if (refreshedGame) {
+          setCurrentGame(refreshedGame);
+        }
         await new Promise(resolve => setTimeout(resolve, 2000));
       }
 
       return result;
     } catch (err) {
-      console.error("Error accepting defeat:", err);
-      setError(err instanceof Error ? err.message : "Failed to accept defeat");
+      console.error("Error claiming draw:", err);
+      setError(err instanceof Error ? err.message : "Failed to claim draw");
       return null;
     } finally {
       setLoading(false);
     }
-  }, [client, account, queryClient]);
+  }, [client, account, fetchGame]);
 
-  const disputeGame = useCallback(async (gameId: string): Promise<ExecuteResult | null> => {
-    if (!client || !account || !queryClient) {
+  const resignGame = useCallback(async (gameId: number): Promise<ExecuteResult | null> => {
+    if (!client || !account) {
       setError("Client not available");
       return null;
     }
@@ -367,26 +339,12 @@ export function useChessGame(): UseChessGameReturn {
     setError(null);
 
     try {
-      const gameResponse = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
-        get_game: { game_id: gameId }
-      });
-
-      if (!gameResponse || !gameResponse.game) {
-        throw new Error("Game not found");
-      }
-
-      const game = gameResponse.game as ChessGame;
-
-      if (game.status !== 'checkmate_claimed') {
-        throw new Error("Can only dispute checkmate claims");
-      }
-
-      const updatedGame: ChessGame = {
-        ...game,
-        status: 'disputed'
+      const msg = {
+        resign_game: {
+          game_id: gameId
+        }
       };
 
-      const msg = { update: { value: JSON.stringify(updatedGame) } };
       const result = await client.execute(
         account.bech32Address,
         CHESS_CONTRACT_ADDRESS,
@@ -395,21 +353,65 @@ export function useChessGame(): UseChessGameReturn {
       );
 
       if (result.transactionHash) {
-        setCurrentGame(updatedGame);
+        // Refresh the game state
+        const refreshedGame = await fetchGame(gameId);
+        if (refreshedGame) {
+          setCurrentGame(refreshedGame);
+        }
         await new Promise(resolve => setTimeout(resolve, 2000));
       }
 
       return result;
     } catch (err) {
-      console.error("Error disputing game:", err);
-      setError(err instanceof Error ? err.message : "Failed to dispute game");
+      console.error("Error resigning game:", err);
+      setError(err instanceof Error ? err.message : "Failed to resign");
       return null;
     } finally {
       setLoading(false);
     }
-  }, [client, account, queryClient]);
+  }, [client, account, fetchGame]);
+
+  // Every game of a contract listing, `filter` and `player` narrow it down
+  const listGames = useCallback(async (
+    filter: { status: string } | null,
+    player: string | null,
+  ): Promise<{ id: number; game: ChessGame }[]> => {
+    if (!queryClient) {
+      setError("Query client not available");
+      return [];
+    }
+
+    try {
+      const games = await fetchAllGamePages(queryClient, CHESS_CONTRACT_ADDRESS, startAfter => player
+        ? { get_player_games: { player, filter, start_after: startAfter, limit: PAGE_LIMIT } }
+        : { list_games: { filter, start_after: startAfter, limit: PAGE_LIMIT } });
+      return games.map(game => ({ id: game.id, game }));
+    } catch (err) {
+      console.error("Error fetching games:", err);
+      setError(err instanceof Error ? err.message : "Failed to fetch games");
+      return [];
+    }
+  }, [queryClient]);
 
-  const resignGame = useCallback(async (gameId: string): Promise<ExecuteResult | null> => {
+  const fetchAllGames = useCallback(async (): Promise<{ id: number; game: ChessGame }[]> => {
+    return listGames(null, null);
+  }, [listGames]);
+
+  const fetchActiveGames = useCallback(async (): Promise<{ id: number; game: ChessGame }[]> => {
+    return listGames({ status: "active" }, null);
+  }, [listGames]);
+
+  const fetchUserGames = useCallback(async (): Promise<{ id: number; game: ChessGame }[]> => {
+    if (!account?.bech32Address) return [];
+    return listGames(null, account.bech32Address);
+  }, [listGames, account?.bech32Address]);
+
+  const fetchUserActiveGames = useCallback(async (): Promise<{ id: number; game: ChessGame }[]> => {
+    if (!account?.bech32Address) return [];
+    return listGames({ status: "active" }, account.bech32Address);
+  }, [listGames, account?.bech32Address]);
+
+  const acceptChallenge = useCallback(async (challengeId: number): Promise<ExecuteResult | null> => {
     if (!client || !account) {
       setError("Client not available");
       return null;
@@ -420,8 +422,8 @@ export function useChessGame(): UseChessGameReturn {
 
     try {
       const msg = {
-        resign_game: {
-          game_id: gameId
+        accept_challenge: {
+          challenge_id: challengeId
         }
       };
 
@@ -432,86 +434,66 @@ export function useChessGame(): UseChessGameReturn {
         "auto"
       );
 
-      if (result.transactionHash) {
-        // Refresh the game state
-        const refreshedGame = await fetchGame(gameId);
-        if (refreshedGame) {
-          setCurrentGame(refreshedGame);
+      // The contract assigns the game id, it is reported in the `game_id` attribute
+      const gameId = findAttribute(result, "game_id");
+      if (result.transactionHash && gameId) {
+        const game = await fetchGame(Number(gameId));
+        if (game) {
+          setCurrentGame(game);
         }
-        await new Promise(resolve => setTimeout(resolve, 2000));
+        const activeGames = await fetchUserActiveGames();
+        setUserActiveGames(activeGames);
+        await fetchIncomingChallenges();
       }
 
       return result;
     } catch (err) {
-      console.error("Error resigning game:", err);
-      setError(err instanceof Error ? err.message : "Failed to resign");
+      console.error("Error accepting challenge:", err);
+      setError(err instanceof Error ? err.message : "Failed to accept challenge");
       return null;
     } finally {
       setLoading(false);
     }
-  }, [client, account, fetchGame]);
+  }, [client, account, fetchGame, fetchUserActiveGames, fetchIncomingChallenges]);
 
-  const fetchAllGames = useCallback(async (): Promise<{ id: string; game: ChessGame }[]> => {
-    if (!queryClient) {
-      setError("Query client not available");
-      return [];
+  const declineChallenge = useCallback(async (challengeId: number): Promise<ExecuteResult | null> => {
+    if (!client || !account) {
+      setError("Client not available");
+      return null;
     }
 
-    try {
-      const response = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
-        get_all_game_ids: {}
-      });
+    setLoading(true);
+    setError(null);
 
-      const games: { id: string; game: ChessGame }[] = [];
-      const gameIds = response.game_ids;
-      
-      for (const gameId of gameIds) {
-        try {
-          const gameData = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
-            get_game: { game_id: gameId }
-          });
-          if (gameData.game) {
-            const game = gameData.game as ChessGame;
-            games.push({ id: gameId, game });
-          }
-        } catch (err) {
-          console.error(`Error fetching game ${gameId}:`, err);
+    try {
+      const msg = {
+        decline_challenge: {
+          challenge_id: challengeId
         }
+      };
+
+      const result = await client.execute(
+        account.bech32Address,
+        CHESS_CONTRACT_ADDRESS,
+        msg,
+        "auto"
+      );
+
+      if (result.transactionHash) {
+        await fetchIncomingChallenges();
       }
 
-      return games;
+      return result;
     } catch (err) {
-      console.error("Error fetching all games:", err);
-      setError(err instanceof Error ? err.message : "Failed to fetch games");
-      return [];
+      console.error("Error declining challenge:", err);
+      setError(err instanceof Error ? err.message : "Failed to decline challenge");
+      return null;
+    } finally {
+      setLoading(false);
     }
-  }, [queryClient]);
-
-  const fetchActiveGames = useCallback(async (): Promise<{ id: string; game: ChessGame }[]> => {
-    const allGames = await fetchAllGames();
-    return allGames.filter(({ game }) => game.status === 'active');
-  }, [fetchAllGames]);
+  }, [client, account, fetchIncomingChallenges]);
 
-  const fetchUserGames = useCallback(async (): Promise<{ id: string; game: ChessGame }[]> => {
-    if (!account?.bech32Address) return [];
-    
-    const allGames = await fetchAllGames();
-    return allGames.filter(({ game }) => 
-      game.white === account.bech32Address || game.black === account.bech32Address
-    );
-  }, [fetchAllGames, account?.bech32Address]);
-
-  const fetchUserActiveGames = useCallback(async (): Promise<{ id: string; game: ChessGame }[]> => {
-    if (!account?.bech32Address) return [];
-    
-    const allGames = await fetchAllGames();
-    return allGames.filter(({ game }) => 
-      game.status === 'active' && 
-      (game.white === account.bech32Address || game.black === account.bech32Address)
-    );
-  }, [fetchAllGames, account?.bech32Address]);
-
-  const selectGame = useCallback((gameId: string) => {
+  const selectGame = useCallback((gameId: number) => {
     const selectedGame = userActiveGames.find(({ id }) => id === gameId);
     if (selectedGame) {
       setCurrentGame(selectedGame.game);
@@ -533,7 +515,7 @@ export function useChessGame(): UseChessGameReturn {
     }
   }, [account?.bech32Address, queryClient, fetchUserActiveGames]);
 
-  const checkTimeStatus = useCallback(async (gameId: string): Promise<TimeStatus | null> => {
+  const checkTimeStatus = useCallback(async (gameId: number): Promise<TimeStatus | null> => {
     if (!queryClient) {
       setError("Query client not available");
       return null;
@@ -561,6 +543,7 @@ export function useChessGame(): UseChessGameReturn {
         try {
           const activeGames = await fetchUserActiveGames();
           setUserActiveGames(activeGames);
+          await fetchIncomingChallenges();
           
           // If no current game is selected, auto-select the first active game
           if (!currentGame && activeGames.length > 0) {
@@ -573,9 +556,9 @@ export function useChessGame(): UseChessGameReturn {
     };
 
     discoverUserGames();
-  }, [account?.bech32Address, queryClient, fetchUserActiveGames, currentGame]);
+  }, [account?.bech32Address, queryClient, fetchUserActiveGames, fetchIncomingChallenges, currentGame]);
 
-  const proposeDraw = useCallback(async (gameId: string): Promise<ExecuteResult | null> => {
+  const proposeDraw = useCallback(async (gameId: number): Promise<ExecuteResult | null> => {
     if (!client || !account) {
       setError("Client not available");
       return null;
@@ -617,7 +600,7 @@ export function useChessGame(): UseChessGameReturn {
     }
   }, [client, account, fetchGame]);
 
-  const respondToDraw = useCallback(async (gameId: string, accept: boolean): Promise<ExecuteResult | null> => {
+  const respondToDraw = useCallback(async (gameId: number, accept: boolean): Promise<ExecuteResult | null> => {
     if (!client || !account) {
       setError("Client not available");
       return null;
@@ -662,7 +645,7 @@ export function useChessGame(): UseChessGameReturn {
 
   // Add periodic time status updates and game state refresh with page visibility detection
   useEffect(() => {
-    if (!currentGame || currentGame.status !== 'active' || !queryClient) return;
+    if (!currentGame || !isGameActive(currentGame.status) || !queryClient) return;
 
     let timeInterval: NodeJS.Timeout;
     let gameInterval: NodeJS.Timeout;
@@ -679,7 +662,7 @@ export function useChessGame(): UseChessGameReturn {
       gameInterval = setInterval(async () => {
         if (!document.hidden) {
           const refreshedGame = await fetchGame(currentGame.id);
-          if (refreshedGame && refreshedGame.moves !== currentGame.moves) {
+          if (refreshedGame && refreshedGame.move_count !== currentGame.move_count) {
             // Game state changed (opponent made a move)
             setCurrentGame(refreshedGame);
           }
@@ -730,12 +713,15 @@ export function useChessGame(): UseChessGameReturn {
     error,
     moveError,
     timeStatus,
+    incomingChallenges,
     createGame,
+    fetchIncomingChallenges,
+    acceptChallenge,
+    declineChallenge,
     fetchGame,
     makeMove,
-    claimVictory,
-    acceptDefeat,
-    disputeGame,
+    claimTimeout,
+    claimDraw,
     resignGame,
     fetchAllGames,
     fetchActiveGames,
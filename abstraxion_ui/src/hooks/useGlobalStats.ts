@@ -1,5 +1,6 @@
 import { useState, useEffect, useCallback } from "react";
 import { useAbstraxionClient } from "@burnt-labs/abstraxion";
+import { fetchAllGamePages, fetchFullLeaderboard, PAGE_LIMIT } from "./contractListings";
 
 const USER_CONTRACT_ADDRESS = process.env.NEXT_PUBLIC_CONTRACT_ADDRESS || "";
 const CHESS_CONTRACT_ADDRESS = process.env.NEXT_PUBLIC_CHESS_GAME_ADDRESS || "";
@@ -28,25 +29,24 @@ export function useGlobalStats(): UseGlobalStatsReturn {
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
 
-  const fetchTotalGames = useCallback(async (): Promise<number> => {
-    if (!queryClient) return 0;
+  // Game count and moves played over all games, from one walk through the game listing
+  const fetchGameTotals = useCallback(async (): Promise<{ totalGames: number; totalMoves: number }> => {
+    if (!queryClient) return { totalGames: 0, totalMoves: 0 };
     if (!CHESS_CONTRACT_ADDRESS) {
       console.warn("Chess contract address is empty");
-      return 0;
+      return { totalGames: 0, totalMoves: 0 };
     }
 
     try {
-      console.log("Fetching total games from:", CHESS_CONTRACT_ADDRESS);
-      const response = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
-        get_all_game_ids: {}
-      });
-      
-      // Count all game IDs
-      const gameCount = (response.game_ids as string[]).length;
-      return gameCount;
+      console.log("Fetching game totals from:", CHESS_CONTRACT_ADDRESS);
+      const games = await fetchAllGamePages(queryClient, CHESS_CONTRACT_ADDRESS, startAfter => ({
+        list_games: { start_after: startAfter, limit: PAGE_LIMIT },
+      }));
+      const totalMoves = games.reduce((sum, game) => sum + game.move_count, 0);
+      return { totalGames: games.length, totalMoves };
     } catch (err) {
-      console.error("Error fetching total games:", err);
-      return 0;
+      console.error("Error fetching game totals:", err);
+      return { totalGames: 0, totalMoves: 0 };
     }
   }, [queryClient]);
 
@@ -59,81 +59,15 @@ export function useGlobalStats(): UseGlobalStatsReturn {
 
     try {
       console.log("Fetching active players from:", CHESS_CONTRACT_ADDRESS);
-      
-      // Get all users from the chess contract
-      const usersResponse = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
-        get_all_users: {}
-      });
-      
-      let activeCount = 0;
-
-      // Check each user's profile
-      for (const userAddress of usersResponse.users) {
-        try {
-          const profileResponse = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
-            get_user_profile: { address: userAddress }
-          });
-          
-          if (profileResponse.profile && profileResponse.profile.games_played > 0) {
-            activeCount++;
-          }
-        } catch (err) {
-          // Skip users without profiles
-          continue;
-        }
-      }
-
-      return activeCount;
+      // The leaderboard lists every profile, players count once they finished a game
+      const entries = await fetchFullLeaderboard(queryClient, CHESS_CONTRACT_ADDRESS);
+      return entries.filter(entry => entry.games_played > 0).length;
     } catch (err) {
       console.error("Error fetching active players:", err);
       return 0;
     }
   }, [queryClient]);
 
-  const fetchTotalMoves = useCallback(async (): Promise<number> => {
-    if (!queryClient) return 0;
-    if (!CHESS_CONTRACT_ADDRESS) {
-      console.warn("Chess contract address is empty for moves");
-      return 0;
-    }
-
-    try {
-      console.log("Fetching total moves from:", CHESS_CONTRACT_ADDRESS);
-      // First get all game IDs
-      const response = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
-        get_all_game_ids: {}
-      });
-      
-      const gameIds = response.game_ids as string[];
-      let totalMoves = 0;
-
-      for (const gameId of gameIds) {
-        try {
-          const gameResponse = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
-            get_game: { game_id: gameId }
-          });
-          
-          if (gameResponse.game) {
-            const game = gameResponse.game;
-            if (game.moves) {
-              // Count moves by splitting on comma
-              const moveCount = game.moves.split(',').filter((m: string) => m.trim()).length;
-              totalMoves += moveCount;
-            }
-          }
-        } catch (err) {
-          // Skip games that can't be fetched
-          continue;
-        }
-      }
-
-      return totalMoves;
-    } catch (err) {
-      console.error("Error fetching total moves:", err);
-      return 0;
-    }
-  }, [queryClient]);
-
   const refreshStats = useCallback(async () => {
     if (!queryClient) {
       setError("Query client not available");
@@ -144,10 +78,9 @@ export function useGlobalStats(): UseGlobalStatsReturn {
     setError(null);
 
     try {
-      const [totalGames, activePlayers, totalMoves] = await Promise.all([
-        fetchTotalGames(),
+      const [{ totalGames, totalMoves }, activePlayers] = await Promise.all([
+        fetchGameTotals(),
         fetchActivePlayers(),
-        fetchTotalMoves(),
       ]);
 
       setStats({
@@ -161,7 +94,7 @@ export function useGlobalStats(): UseGlobalStatsReturn {
     } finally {
       setLoading(false);
     }
-  }, [queryClient, fetchTotalGames, fetchActivePlayers, fetchTotalMoves]);
+  }, [queryClient, fetchGameTotals, fetchActivePlayers]);
 
   // Auto-refresh stats when client is available
   useEffect(() => {
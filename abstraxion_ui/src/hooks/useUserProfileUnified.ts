@@ -5,18 +5,21 @@ import {
   useAbstraxionSigningClient,
   useAbstraxionClient,
 } from "@burnt-labs/abstraxion";
-import type { ChessUser } from "../types/chess";
+import type { ChessUser, LeaderboardEntry } from "../types/chess";
 
 const CHESS_CONTRACT_ADDRESS = process.env.NEXT_PUBLIC_CHESS_GAME_ADDRESS || "";
 
+// Players shown on the leaderboard
+const LEADERBOARD_SIZE = 10;
+
 interface UseUserProfileReturn {
   userProfile: ChessUser | null;
-  allUsers: ChessUser[];
+  leaderboard: LeaderboardEntry[];
   loading: boolean;
   error: string | null;
   initializeUser: (username?: string) => Promise<ExecuteResult | null>;
   refreshProfile: () => Promise<void>;
-  refreshAllUsers: () => Promise<void>;
+  refreshLeaderboard: () => Promise<void>;
 }
 
 export function useUserProfileUnified(): UseUserProfileReturn {
@@ -25,7 +28,7 @@ export function useUserProfileUnified(): UseUserProfileReturn {
   const { client: queryClient } = useAbstraxionClient();
   
   const [userProfile, setUserProfile] = useState<ChessUser | null>(null);
-  const [allUsers, setAllUsers] = useState<ChessUser[]>([]);
+  const [leaderboard, setLeaderboard] = useState<LeaderboardEntry[]>([]);
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
 
@@ -101,52 +104,20 @@ export function useUserProfileUnified(): UseUserProfileReturn {
     }
   }, [queryClient, account?.bech32Address]);
 
-  const refreshAllUsers = useCallback(async () => {
+  const refreshLeaderboard = useCallback(async () => {
     if (!queryClient) {
       return;
     }
 
     try {
-      // Get all user addresses
-      const usersResponse = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
-        get_all_users: {}
+      // The contract ranks players itself, only the shown top ten are fetched
+      const response = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
+        leaderboard: { limit: LEADERBOARD_SIZE, include_provisional: true }
       });
-
-      const users: ChessUser[] = [];
-      
-      // Fetch each user's profile
-      for (const userAddress of usersResponse.users) {
-        try {
-          const profileResponse = await queryClient.queryContractSmart(CHESS_CONTRACT_ADDRESS, {
-            get_user_profile: { address: userAddress }
-          });
-
-          if (profileResponse.profile) {
-            const user: ChessUser = {
-              address: userAddress,
-              username: profileResponse.profile.username,
-              elo: profileResponse.profile.elo,
-              games_played: profileResponse.profile.games_played,
-              wins: profileResponse.profile.wins,
-              draws: profileResponse.profile.draws,
-              losses: profileResponse.profile.losses,
-              current_games: profileResponse.profile.current_games,
-              created_at: profileResponse.profile.created_at.toString(),
-            };
-            users.push(user);
-          }
-        } catch (err) {
-          console.error(`Error fetching profile for ${userAddress}:`, err);
-          // Continue with other users
-        }
-      }
-
-      // Sort by ELO rating (highest first)
-      users.sort((a, b) => b.elo - a.elo);
-      setAllUsers(users);
+      setLeaderboard(response.entries as LeaderboardEntry[]);
     } catch (err) {
-      console.error("Error fetching all users:", err);
-      setError(err instanceof Error ? err.message : "Failed to fetch users");
+      console.error("Error fetching leaderboard:", err);
+      setError(err instanceof Error ? err.message : "Failed to fetch leaderboard");
     }
   }, [queryClient]);
 
@@ -159,11 +130,11 @@ export function useUserProfileUnified(): UseUserProfileReturn {
 
   return {
     userProfile,
-    allUsers,
+    leaderboard,
     loading,
     error,
     initializeUser,
     refreshProfile,
-    refreshAllUsers,
+    refreshLeaderboard,
   };
 }
\ No newline at end of file
@@ -6,35 +6,111 @@ export interface ChessUser {
   wins: number;
   draws: number;
   losses: number;
-  current_games: string[];
+  current_games: number[];
   created_at: string;
 }
 
+export type Termination =
+  | 'checkmate'
+  | 'resignation'
+  | 'timeout'
+  | 'stalemate'
+  | 'insufficient_material'
+  | 'agreement'
+  | 'threefold_repetition'
+  | 'fifty_move_rule'
+  | 'fivefold_repetition'
+  | 'seventy_five_move_rule'
+  | 'adjudication'
+  | 'aborted'
+  | 'unknown';
+
+// Serialized form of the contract's GameState enum
+export type GameState =
+  | 'active'
+  | 'aborted'
+  | { white_won: { reason: Termination } }
+  | { black_won: { reason: Termination } }
+  | { draw: { reason: Termination } };
+
+export type DrawClaim = 'threefold_repetition' | 'fifty_move_rule';
+
 export interface ChessGame {
-  id: string;
+  id: number;  // Assigned by the contract
   white: string;
   black: string;
-  moves: string;
   current_fen: string;  // Current board position in FEN notation
-  status: 'active' | 'checkmate_claimed' | 'disputed' | 'white_won' | 'black_won' | 'draw' | 'stalemate' | 'timeout';
+  status: GameState;
   current_turn: 'white' | 'black';
   last_move_block: number;
   white_time_remaining: number;  // Blocks remaining for white
   black_time_remaining: number;  // Blocks remaining for black
   created_block: number;
   claim_block: number | null;
-  time_control: string;
+  time_control: TimeControl;
+  clock_unit: ClockUnit;
+  rated: boolean;
   move_count: number;  // Total moves made in the game
   draw_proposed_by: string | null;  // Address of player who proposed draw
 }
 
+export type ClockUnit = 'blocks' | 'seconds';
+
+// Durations are in the game's clock unit
+export type TimeControl =
+  | { fischer: { initial: number; increment: number } }
+  | { bronstein: { initial: number; delay: number } }
+  | { correspondence: { per_move: number } }
+  | 'unlimited';
+
+export type ColorChoice = 'white' | 'black' | 'random';
+
+// A game offer, the game only starts once the opponent accepts it
+export interface Challenge {
+  id: number;
+  challenger: string;
+  opponent: string | null;  // null for open challenges
+  challenger_color: ColorChoice;
+  time_control: TimeControl;
+  clock_unit: ClockUnit;
+  rated: boolean;
+  created_block: number;
+  color_commitment: string | null;  // base64 sha256 of the challenger's secret, random colors only
+  pending_reveal: { opponent: string; entropy: string; deadline: number } | null;
+}
+
+export interface LeaderboardEntry {
+  rank: number;
+  address: string;
+  username: string;
+  rating: number;
+  games_played: number;
+}
+
+// Where the next leaderboard page starts, returned by the contract
+export interface LeaderboardCursor {
+  rating: number;
+  address: string;
+  rank: number;
+}
+
 export interface TimeStatus {
+  // Remaining time in the game's clock unit, already counting the running clock down
   white_time_remaining: number;
   black_time_remaining: number;
-  current_player: string;
+  current_player: 'white' | 'black';
   time_expired: boolean;
   move_count: number;
   time_since_last_move: number;
+  time_control: TimeControl;
+  clock_unit: ClockUnit;
+  // Remaining time in both units, converted with the contract's average block time
+  white_seconds_remaining: number;
+  black_seconds_remaining: number;
+  white_blocks_remaining: number;
+  black_blocks_remaining: number;
+  seconds_since_last_move: number;
+  blocks_since_last_move: number;
 }
 
 export interface ChessMove {
@@ -42,4 +118,25 @@ export interface ChessMove {
   to: string;
   promotion?: string;
   resulting_fen?: string;  // FEN after this move is applied
-}
\ No newline at end of file
+}
+
+export function isGameActive(status: GameState): boolean {
+  return status === 'active';
+}
+
+export function gameWinner(status: GameState): 'white' | 'black' | null {
+  if (typeof status === 'string') return null;
+  if ('white_won' in status) return 'white';
+  if ('black_won' in status) return 'black';
+  return null;
+}
+
+// Human readable result, e.g. "WHITE WON (checkmate)"
+export function describeGameState(status: GameState): string {
+  if (typeof status === 'string') return status.toUpperCase();
+  const [result, reason] =
+    'white_won' in status ? ['WHITE WON', status.white_won.reason] :
+    'black_won' in status ? ['BLACK WON', status.black_won.reason] :
+    ['DRAW', status.draw.reason];
+  return `${result} (${reason.replace(/_/g, ' ')})`;
+}
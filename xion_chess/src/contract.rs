@@ -1,13 +1,23 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{from_json, to_json_binary, Addr, Attribute, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult, Storage, Uint128, WasmMsg};
+use cw20::{Cw20CoinVerified, Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw721::msg::{Cw721ExecuteMsg, NftExtensionMsg};
+use cw721::state::Trait;
 use cw2::set_contract_version;
-use shakmaty::{Chess, Position, Move, Role};
+use cw_utils::nonpayable;
+use shakmaty::{Chess, Position, Move, Role, Square};
+use shakmaty::uci::UciMove;
+use shakmaty::zobrist::{Zobrist64, ZobristHash};
 use shakmaty::fen::Fen;
+use shakmaty::san::SanPlus;
+use sha2::{Digest, Sha256};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, GameStatus, VerificationResponse, MoveValidationResponse, GameResponse, GamesResponse, GameIdsResponse, TimeStatusResponse, UserProfileResponse, UsersResponse};
-use crate::state::{ChessGame, UserProfile, GAMES, GAME_IDS, USER_PROFILES, USER_ADDRESSES};
+use crate::move_encoding::{load_moves, pack_move, push_move};
+use crate::rating::{elo_update, glicko2_update, Glicko2, SCORE_SCALE};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, ReceiveMsg, CategoryRating, ChallengeResponse, ChallengesResponse, ConfigResponse, DrawClaim, GameCreatedResponse, GameFilter, GameStatus, SortOrder, StatusFilter, VerificationResponse, MoveValidationResponse, GameResponse, GamesResponse, GameIdsResponse, LeaderboardCursor, LeaderboardEntry, LeaderboardResponse, MoveHistoryEntry, MoveHistoryResponse, PgnResponse, RatingHistoryResponse, TimeStatusResponse, UserProfileResponse, UsersResponse};
+use crate::state::{default_avg_block_time_ms, Challenge, ChessGame, ClockUnit, Color, ColorChoice, Config, GameState, MoveRecord, Rating, RatingCategory, RatingChange, RatingSystem, Termination, TimeControl, TimeControlBounds, PendingReveal, UserProfile, Wager, category_ratings, COLOR_REVEAL_BLOCKS, CONFIG, GAMES, GAME_COUNT, GAME_NFTS, CHALLENGES, CHALLENGE_COUNT, LEGACY_GAMES, LEGACY_GAME_IDS, LEGACY_GAME_INDEX, LEGACY_USER_PROFILES, MOVE_HISTORY, POSITION_COUNTS, RATING_HISTORY, USER_PROFILES, USER_ADDRESSES};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:xion_chess";
@@ -18,13 +28,33 @@ pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    _msg: InstantiateMsg,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender.clone(),
+    };
+    let config = Config {
+        admin: admin.clone(),
+        time_control_bounds: msg.time_control_bounds.unwrap_or_default(),
+        avg_block_time_ms: msg.avg_block_time_ms.unwrap_or_else(default_avg_block_time_ms),
+        rating_system: msg.rating_system.unwrap_or_default(),
+        house_fee_bps: msg.house_fee_bps.unwrap_or_default(),
+        treasury: msg.treasury.map(|treasury| deps.api.addr_validate(&treasury)).transpose()?,
+        cw20_allowlist: msg.cw20_allowlist.unwrap_or_default().iter()
+            .map(|address| deps.api.addr_validate(address))
+            .collect::<StdResult<_>>()?,
+        nft_contract: msg.nft_contract.map(|nft_contract| deps.api.addr_validate(&nft_contract)).transpose()?,
+    };
+    execute::validate_house_fee(&config)?;
+    CONFIG.save(deps.storage, &config)?;
+
     Ok(Response::new()
         .add_attribute("method", "instantiate")
-        .add_attribute("owner", info.sender))
+        .add_attribute("owner", info.sender)
+        .add_attribute("admin", admin))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -34,6 +64,10 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Only challenges escrow native funds, anything sent with other messages would be stuck
+    if !matches!(msg, ExecuteMsg::CreateChallenge { .. } | ExecuteMsg::AcceptChallenge { .. }) {
+        nonpayable(&info)?;
+    }
     match msg {
         ExecuteMsg::InitializeUser { username } => {
             execute::initialize_user(deps, env, info, username)
@@ -41,23 +75,58 @@ pub fn execute(
         ExecuteMsg::VerifyPosition { fen, claimed_status } => {
             execute::verify_position(fen, claimed_status)
         }
-        ExecuteMsg::CreateGame { game_id, opponent, time_control } => {
-            execute::create_game(deps, env, info, game_id, opponent, time_control)
+        ExecuteMsg::CreateChallenge { opponent, color, time_control, clock_unit, rated, color_commitment } => {
+            let wager = execute::wager_from_funds(&info.funds)?;
+            execute::create_challenge(deps, env, info.sender, wager, opponent, color, time_control, clock_unit.unwrap_or_default(), rated, color_commitment)
+        }
+        ExecuteMsg::AcceptChallenge { challenge_id, entropy } => {
+            let wager = execute::wager_from_funds(&info.funds)?;
+            execute::accept_challenge(deps, env, info.sender, wager, challenge_id, entropy)
+        }
+        ExecuteMsg::RevealColor { challenge_id, secret } => {
+            execute::reveal_color(deps, env, challenge_id, secret)
+        }
+        ExecuteMsg::ClaimRevealTimeout { challenge_id } => {
+            execute::claim_reveal_timeout(deps, env, info, challenge_id)
+        }
+        ExecuteMsg::Receive(msg) => {
+            execute::receive_cw20(deps, env, info, msg)
+        }
+        ExecuteMsg::DeclineChallenge { challenge_id } => {
+            execute::decline_challenge(deps, info, challenge_id)
+        }
+        ExecuteMsg::CancelChallenge { challenge_id } => {
+            execute::cancel_challenge(deps, info, challenge_id)
         }
         ExecuteMsg::MakeMove { game_id, from, to, promotion } => {
             execute::make_move(deps, env, info, game_id, from, to, promotion)
         }
-        ExecuteMsg::UpdateGameStatus { game_id, status } => {
-            execute::update_game_status(deps, info, game_id, status)
+        ExecuteMsg::ClaimTimeout { game_id } => {
+            execute::claim_timeout(deps, env, info, game_id)
+        }
+        ExecuteMsg::ClaimDraw { game_id, reason } => {
+            execute::claim_draw(deps, env, info, game_id, reason)
+        }
+        ExecuteMsg::AbortGame { game_id } => {
+            execute::abort_game(deps, env, info, game_id)
+        }
+        ExecuteMsg::AdjudicateGame { game_id, result } => {
+            execute::adjudicate_game(deps, env, info, game_id, result)
+        }
+        ExecuteMsg::UpdateConfig { admin, time_control_bounds, avg_block_time_ms, rating_system, house_fee_bps, treasury, clear_treasury, cw20_allowlist, nft_contract } => {
+            execute::update_config(deps, info, admin, time_control_bounds, avg_block_time_ms, rating_system, house_fee_bps, treasury, clear_treasury, cw20_allowlist, nft_contract)
         }
         ExecuteMsg::ResignGame { game_id } => {
-            execute::resign_game(deps, info, game_id)
+            execute::resign_game(deps, env, info, game_id)
         }
         ExecuteMsg::ProposeDrawRequest { game_id } => {
             execute::propose_draw(deps, info, game_id)
         }
         ExecuteMsg::RespondToDrawRequest { game_id, accept } => {
-            execute::respond_to_draw(deps, info, game_id, accept)
+            execute::respond_to_draw(deps, env, info, game_id, accept)
+        }
+        ExecuteMsg::MintGameNft { game_id } => {
+            execute::mint_game_nft(deps, env, info, game_id)
         }
     }
 }
@@ -65,125 +134,312 @@ pub fn execute(
 pub mod execute {
     use super::*;
 
-    /// Calculate new ELO ratings after a game using integer arithmetic
-    /// Uses scaled integers (x1000) to avoid floating point operations
-    /// k_factor is 32, scores: win=1000, draw=500, loss=0
-    fn calculate_elo(winner_elo: u32, loser_elo: u32, is_draw: bool) -> (u32, u32) {
-        let k_factor = 32u32;
-        let scale = 1000u32; // Scale factor for precision
-        
-        // Calculate rating difference (clamped to prevent overflow)
-        let rating_diff = if winner_elo >= loser_elo {
-            (winner_elo - loser_elo).min(800) // Cap at 800 to prevent overflow
-        } else {
-            (loser_elo - winner_elo).min(800)
-        };
-        
-        // Simplified ELO expected score calculation using integer approximation
-        // For rating differences, use lookup table approach
-        let expected_winner_scaled = match rating_diff {
-            0..=25 => 500,      // ~0.50
-            26..=50 => 537,     // ~0.537
-            51..=100 => 640,    // ~0.64
-            101..=150 => 691,   // ~0.691
-            151..=200 => 760,   // ~0.76
-            201..=300 => 849,   // ~0.849
-            301..=400 => 909,   // ~0.909
-            _ => 950,           // ~0.95 for large differences
-        };
-        
-        // If loser had higher rating, invert expectation
-        let (winner_expected, loser_expected) = if winner_elo >= loser_elo {
-            (expected_winner_scaled, scale - expected_winner_scaled)
-        } else {
-            (scale - expected_winner_scaled, expected_winner_scaled)
-        };
-        
-        // Set actual scores
-        let (winner_actual, loser_actual) = if is_draw {
-            (scale / 2, scale / 2) // Both get 0.5
+    /// Calculate new Elo ratings after a game with each player's own K-factor.
+    /// `white_score` is in `SCORE_SCALE` units: win=1000, draw=500, loss=0
+    fn calculate_elo(white: &Rating, black: &Rating, white_score: u32) -> Result<(u32, u32), ContractError> {
+        Ok((
+            elo_update(white.elo, black.elo, white_score, white.k_factor())?,
+            elo_update(black.elo, white.elo, SCORE_SCALE - white_score, black.k_factor())?,
+        ))
+    }
+
+    /// Set the final state of a game and apply the result to both player profiles
+    fn finish_game(
+        deps: &mut DepsMut,
+        env: &Env,
+        game: &mut ChessGame,
+        outcome: GameState,
+    ) -> Result<(), ContractError> {
+        game.status = outcome;
+        game.draw_proposed_by = None;
+        clear_positions(deps.storage, game.id)?;
+        update_profiles_after_game(deps, env, game)
+    }
+
+    /// Count another occurrence of `pos` in the game, returning how often it has occurred.
+    /// After a pawn move or capture no earlier position can come back, so their counts are
+    /// dropped first.
+    fn record_position(storage: &mut dyn Storage, game_id: u64, pos: &Chess) -> StdResult<u32> {
+        if pos.halfmoves() == 0 {
+            clear_positions(storage, game_id)?;
+        }
+        let key = (game_id, query::position_hash(pos));
+        let occurrences = POSITION_COUNTS.may_load(storage, key)?.unwrap_or_default() + 1;
+        POSITION_COUNTS.save(storage, key, &occurrences)?;
+        Ok(occurrences)
+    }
+
+    /// Forget the repetition counts of a game
+    fn clear_positions(storage: &mut dyn Storage, game_id: u64) -> StdResult<()> {
+        let hashes = POSITION_COUNTS
+            .prefix(game_id)
+            .keys(storage, None, None, cosmwasm_std::Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for hash in hashes {
+            POSITION_COUNTS.remove(storage, (game_id, hash));
+        }
+        Ok(())
+    }
+
+    /// Check a time control against the admin configured bounds
+    fn validate_time_control(time_control: &TimeControl, clock_unit: ClockUnit, config: &Config) -> Result<(), ContractError> {
+        let bounds = &config.time_control_bounds;
+        let invalid = |reason: &str| Err(ContractError::InvalidTimeControl { reason: reason.to_string() });
+        let seconds = |duration: u64| query::to_seconds(duration, clock_unit, config.avg_block_time_ms);
+        match *time_control {
+            TimeControl::Fischer { initial, increment: extra } | TimeControl::Bronstein { initial, delay: extra } => {
+                if seconds(initial) < bounds.min_initial || seconds(initial) > bounds.max_initial {
+                    return invalid("initial time out of bounds");
+                }
+                if seconds(extra) > bounds.max_increment {
+                    return invalid("increment or delay too large");
+                }
+            }
+            TimeControl::Correspondence { per_move } => {
+                if seconds(per_move) < bounds.min_per_move || seconds(per_move) > bounds.max_per_move {
+                    return invalid("time per move out of bounds");
+                }
+            }
+            TimeControl::Unlimited => {
+                if !bounds.allow_unlimited {
+                    return invalid("unlimited games are disabled");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Color of the challenger in a random color challenge. The challenger committed to
+    /// `secret` before the opponent chose `entropy`, so neither could steer the hash.
+    pub fn random_color(secret: &[u8], entropy: &[u8]) -> Color {
+        let digest = Sha256::new()
+            .chain_update(secret)
+            .chain_update(entropy)
+            .finalize();
+        if digest[0] & 1 == 0 { Color::White } else { Color::Black }
+    }
+
+    /// Color the sender plays in this game
+    fn player_color(game: &ChessGame, sender: &cosmwasm_std::Addr) -> Result<Color, ContractError> {
+        if game.white == sender {
+            Ok(Color::White)
+        } else if game.black == sender {
+            Ok(Color::Black)
         } else {
-            (scale, 0) // Winner gets 1.0, loser gets 0.0
+            Err(ContractError::NotPlayerInGame {})
+        }
+    }
+
+    /// Result attributes emitted whenever a game ends
+    fn game_over_attributes(game: &ChessGame) -> Vec<Attribute> {
+        let winner = match game.status.winner() {
+            Some(Color::White) => game.white.to_string(),
+            Some(Color::Black) => game.black.to_string(),
+            None => String::new(),
         };
-        
-        // Calculate ELO changes: K * (actual - expected) / scale
-        // Use signed arithmetic for the differences
-        let winner_change = (k_factor as i32 * (winner_actual as i32 - winner_expected as i32)) / scale as i32;
-        let loser_change = (k_factor as i32 * (loser_actual as i32 - loser_expected as i32)) / scale as i32;
-        
-        // Apply changes (ensure no underflow below 100)
-        let new_winner_elo = if winner_change >= 0 {
-            winner_elo + winner_change as u32
-        } else {
-            winner_elo.saturating_sub((-winner_change) as u32)
+        vec![
+            Attribute::new("result", game.status.as_str()),
+            Attribute::new("termination", game.status.termination().map(|t| t.as_str()).unwrap_or_default()),
+            Attribute::new("winner", winner),
+        ]
+    }
+
+    /// Native wager escrowed with a message: nothing, or a single non-zero coin
+    pub fn wager_from_funds(funds: &[Coin]) -> Result<Option<Wager>, ContractError> {
+        match funds {
+            [] => Ok(None),
+            [coin] if coin.amount.is_zero() => Err(ContractError::InvalidWager { reason: "stake must not be zero".to_string() }),
+            [coin] => Ok(Some(Wager::Native(coin.clone()))),
+            _ => Err(ContractError::InvalidWager { reason: "only a single denom can be staked".to_string() }),
+        }
+    }
+
+    pub fn validate_house_fee(config: &Config) -> Result<(), ContractError> {
+        if config.house_fee_bps > 10_000 || (config.house_fee_bps > 0 && config.treasury.is_none()) {
+            return Err(ContractError::InvalidHouseFee {});
+        }
+        Ok(())
+    }
+
+    /// Message sending `amount` of the wagered token to `recipient`
+    fn send_stake(wager: &Wager, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        Ok(match wager {
+            Wager::Native(coin) => BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: vec![Coin::new(amount, &coin.denom)],
+            }.into(),
+            Wager::Cw20(token) => WasmMsg::Execute {
+                contract_addr: token.address.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer { recipient: recipient.to_string(), amount })?,
+                funds: vec![],
+            }.into(),
+        })
+    }
+
+    /// Pay out the escrow of a finished game: the winner takes the pot minus the house fee,
+    /// draws and aborted games refund both players
+    fn wager_payouts(storage: &dyn Storage, game: &ChessGame) -> StdResult<Vec<CosmosMsg>> {
+        let Some(stake) = &game.wager else {
+            return Ok(vec![]);
         };
-        
-        let new_loser_elo = if loser_change >= 0 {
-            loser_elo + loser_change as u32
-        } else {
-            loser_elo.saturating_sub((-loser_change) as u32)
+        let winner = match game.status.winner() {
+            Some(Color::White) => &game.white,
+            Some(Color::Black) => &game.black,
+            None => {
+                return [&game.white, &game.black].into_iter()
+                    .map(|player| send_stake(stake, player, stake.amount()))
+                    .collect();
+            }
         };
-        
-        // Ensure ratings don't go below 100 (minimum rating)
-        (new_winner_elo.max(100), new_loser_elo.max(100))
+
+        let config = CONFIG.load(storage)?;
+        let pot = stake.amount() * Uint128::new(2);
+        let fee = pot.multiply_ratio(config.house_fee_bps, 10_000u128);
+        let mut payouts = vec![send_stake(stake, winner, pot - fee)?];
+        if let (false, Some(treasury)) = (fee.is_zero(), config.treasury) {
+            payouts.push(send_stake(stake, &treasury, fee)?);
+        }
+        Ok(payouts)
+    }
+
+    /// Give a challenge's escrow back to the challenger
+    fn refund_challenge(challenge: &Challenge) -> StdResult<Vec<CosmosMsg>> {
+        challenge.wager.iter()
+            .map(|stake| send_stake(stake, &challenge.challenger, stake.amount()))
+            .collect()
+    }
+
+    fn glicko2_of(rating: &Rating) -> Glicko2 {
+        Glicko2 { rating: rating.elo, deviation: rating.deviation, volatility: rating.volatility }
+    }
+
+    fn set_glicko2(rating: &mut Rating, glicko2: Glicko2) {
+        rating.elo = glicko2.rating;
+        rating.deviation = glicko2.deviation;
+        rating.volatility = glicko2.volatility;
+    }
+
+    /// Add a result to the record of a player
+    fn record_result(rating: &mut Rating, score: u32, rated: bool, height: u64) {
+        rating.games_played += 1;
+        if !rated {
+            rating.casual_games += 1;
+        }
+        match score {
+            SCORE_SCALE => rating.wins += 1,
+            0 => rating.losses += 1,
+            _ => rating.draws += 1,
+        }
+        rating.last_game_at = height;
+    }
+
+    /// Update both players' ratings with the configured system, casual games leave them
+    /// untouched, and record the result
+    fn rate_game(white: &mut Rating, black: &mut Rating, white_score: u32, rated: bool, system: RatingSystem, height: u64) -> Result<(), ContractError> {
+        if rated {
+            match system {
+                RatingSystem::Elo => {
+                    (white.elo, black.elo) = calculate_elo(white, black, white_score)?;
+                }
+                RatingSystem::Glicko2 => {
+                    let white_glicko2 = glicko2_of(white);
+                    let black_glicko2 = glicko2_of(black);
+                    set_glicko2(white, glicko2_update(white_glicko2, &[(black_glicko2, white_score)])?);
+                    set_glicko2(black, glicko2_update(black_glicko2, &[(white_glicko2, SCORE_SCALE - white_score)])?);
+                }
+            }
+            white.peak_elo = white.peak_elo.max(white.elo);
+            black.peak_elo = black.peak_elo.max(black.elo);
+        }
+        record_result(white, white_score, rated, height);
+        record_result(black, SCORE_SCALE - white_score, rated, height);
+        Ok(())
     }
 
-    /// Update player profiles after game ends
+    /// Update player profiles and the ratings of the game's category after game ends,
+    /// based on the game's final status
     fn update_profiles_after_game(
         deps: &mut DepsMut,
+        env: &Env,
         game: &ChessGame,
-        white_won: bool,
-        black_won: bool,
-        is_draw: bool,
     ) -> Result<(), ContractError> {
         let white_addr = game.white.clone();
         let black_addr = game.black.clone();
-        
+
         // Load both profiles
         let mut white_profile = USER_PROFILES.load(deps.storage, white_addr.clone())?;
         let mut black_profile = USER_PROFILES.load(deps.storage, black_addr.clone())?;
-        
-        // Calculate new ELO ratings
-        let (new_white_elo, new_black_elo) = if is_draw {
-            calculate_elo(white_profile.elo, black_profile.elo, true)
-        } else if white_won {
-            calculate_elo(white_profile.elo, black_profile.elo, false)
-        } else {
-            let (black_new, white_new) = calculate_elo(black_profile.elo, white_profile.elo, false);
-            (white_new, black_new)
-        };
-        
-        // Update white profile
-        white_profile.elo = new_white_elo;
-        white_profile.games_played += 1;
-        if white_won {
-            white_profile.wins += 1;
-        } else if black_won {
-            white_profile.losses += 1;
-        } else {
-            white_profile.draws += 1;
-        }
         white_profile.current_games.retain(|id| id != &game.id);
-        
-        // Update black profile
-        black_profile.elo = new_black_elo;
-        black_profile.games_played += 1;
-        if black_won {
-            black_profile.wins += 1;
-        } else if white_won {
-            black_profile.losses += 1;
-        } else {
-            black_profile.draws += 1;
-        }
         black_profile.current_games.retain(|id| id != &game.id);
-        
-        // Save updated profiles
-        USER_PROFILES.save(deps.storage, white_addr, &white_profile)?;
-        USER_PROFILES.save(deps.storage, black_addr, &black_profile)?;
-        
+
+        // Aborted games don't count towards ratings or records
+        let white_score = match game.status {
+            GameState::WhiteWon { .. } => SCORE_SCALE,
+            GameState::BlackWon { .. } => 0,
+            GameState::Draw { .. } => SCORE_SCALE / 2,
+            GameState::Active | GameState::Aborted => {
+                USER_PROFILES.save(deps.storage, white_addr, &white_profile)?;
+                USER_PROFILES.save(deps.storage, black_addr, &black_profile)?;
+                return Ok(());
+            }
+        };
+        let system = CONFIG.load(deps.storage)?.rating_system;
+
+        // Overall ratings
+        let white_before = white_profile.overall();
+        let black_before = black_profile.overall();
+        let mut white_rating = white_before.clone();
+        let mut black_rating = black_before.clone();
+        rate_game(&mut white_rating, &mut black_rating, white_score, game.rated, system, env.block.height)?;
+        white_profile.set_overall(white_rating.clone());
+        black_profile.set_overall(black_rating.clone());
+        USER_PROFILES.save(deps.storage, white_addr.clone(), &white_profile)?;
+        USER_PROFILES.save(deps.storage, black_addr.clone(), &black_profile)?;
+
+        // Ratings of the game's time control category
+        let ratings = category_ratings(game.category);
+        let white_category_before = ratings.may_load(deps.storage, white_addr.clone())?.unwrap_or_default();
+        let black_category_before = ratings.may_load(deps.storage, black_addr.clone())?.unwrap_or_default();
+        let mut white_category = white_category_before.clone();
+        let mut black_category = black_category_before.clone();
+        rate_game(&mut white_category, &mut black_category, white_score, game.rated, system, env.block.height)?;
+        ratings.save(deps.storage, white_addr.clone(), &white_category)?;
+        ratings.save(deps.storage, black_addr.clone(), &black_category)?;
+
+        if game.rated {
+            record_rating_change(deps.storage, env, game, &white_addr, &black_addr, (&white_before, &white_rating), (&white_category_before, &white_category))?;
+            record_rating_change(deps.storage, env, game, &black_addr, &white_addr, (&black_before, &black_rating), (&black_category_before, &black_category))?;
+        }
+
         Ok(())
     }
 
+    /// Append a rated game to the rating history of `player`. Entries are numbered by the
+    /// player's rated game count, so they are ordered by the time the games finished.
+    fn record_rating_change(
+        storage: &mut dyn Storage,
+        env: &Env,
+        game: &ChessGame,
+        player: &Addr,
+        opponent: &Addr,
+        (before, after): (&Rating, &Rating),
+        (category_before, category_after): (&Rating, &Rating),
+    ) -> StdResult<()> {
+        let index = after.games_played - after.casual_games;
+        RATING_HISTORY.save(storage, (player.clone(), index), &RatingChange {
+            index,
+            game_id: game.id,
+            opponent: opponent.clone(),
+            category: game.category,
+            old_rating: before.elo,
+            new_rating: after.elo,
+            old_category_rating: category_before.elo,
+            new_category_rating: category_after.elo,
+            block_height: env.block.height,
+            block_time: env.block.time.seconds(),
+        })
+    }
+
     pub fn initialize_user(
         deps: DepsMut,
         env: Env,
@@ -196,13 +452,9 @@ pub mod execute {
         let mut profile = USER_PROFILES.may_load(deps.storage, sender.clone())?
             .unwrap_or_else(|| UserProfile {
                 username: username.clone(),
-                elo: 1200,
-                games_played: 0,
-                wins: 0,
-                draws: 0,
-                losses: 0,
-                current_games: Vec::new(),
                 created_at: env.block.height,
+                last_game_at: env.block.height,
+                ..UserProfile::default()
             });
         
         // Update username if provided
@@ -224,13 +476,7 @@ pub mod execute {
         let verification = query::verify_position_internal(fen.clone())?;
         
         // Check if claimed status matches actual status
-        let status_matches = match (&claimed_status, &verification.status) {
-            (GameStatus::Checkmate, GameStatus::Checkmate) => true,
-            (GameStatus::Stalemate, GameStatus::Stalemate) => true,
-            (GameStatus::Draw, GameStatus::Draw) => true,
-            (GameStatus::Active, GameStatus::Active) => true,
-            _ => false,
-        };
+        let status_matches = claimed_status == verification.status;
 
         if !status_matches {
             return Err(ContractError::InvalidClaim {
@@ -246,52 +492,47 @@ pub mod execute {
             .add_attribute("is_check", verification.is_check.to_string()))
     }
 
-    pub fn create_game(
+    /// Store a new game under the next free id and register it on both profiles
+    #[allow(clippy::too_many_arguments)]
+    fn start_game(
         deps: DepsMut,
-        env: Env,
-        info: MessageInfo,
-        game_id: String,
-        opponent: cosmwasm_std::Addr,
-        time_control: String,
-    ) -> Result<Response, ContractError> {
-        // Check if game already exists
-        if GAMES.has(deps.storage, game_id.clone()) {
-            return Err(ContractError::GameAlreadyExists { id: game_id });
-        }
+        env: &Env,
+        white: cosmwasm_std::Addr,
+        black: cosmwasm_std::Addr,
+        time_control: TimeControl,
+        clock_unit: ClockUnit,
+        rated: bool,
+        wager: Option<Wager>,
+    ) -> Result<ChessGame, ContractError> {
+        // Assign the next game id
+        let game_id = GAME_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+        GAME_COUNT.save(deps.storage, &game_id)?;
 
         // Ensure both players have profiles
-        let white_addr = info.sender.clone();
-        let black_addr = opponent.clone();
+        let white_addr = white.clone();
+        let black_addr = black.clone();
         
         // Initialize white player if needed
         let mut white_profile = USER_PROFILES.may_load(deps.storage, white_addr.clone())?
             .unwrap_or_else(|| UserProfile {
                 username: white_addr.to_string(),
-                elo: 1200,
-                games_played: 0,
-                wins: 0,
-                draws: 0,
-                losses: 0,
-                current_games: Vec::new(),
                 created_at: env.block.height,
+                last_game_at: env.block.height,
+                ..UserProfile::default()
             });
         
         // Initialize black player if needed
         let mut black_profile = USER_PROFILES.may_load(deps.storage, black_addr.clone())?
             .unwrap_or_else(|| UserProfile {
                 username: black_addr.to_string(),
-                elo: 1200,
-                games_played: 0,
-                wins: 0,
-                draws: 0,
-                losses: 0,
-                current_games: Vec::new(),
                 created_at: env.block.height,
+                last_game_at: env.block.height,
+                ..UserProfile::default()
             });
         
         // Add game to both players' current games
-        white_profile.current_games.push(game_id.clone());
-        black_profile.current_games.push(game_id.clone());
+        white_profile.current_games.push(game_id);
+        black_profile.current_games.push(game_id);
         
         // Save updated profiles
         USER_PROFILES.save(deps.storage, white_addr.clone(), &white_profile)?;
@@ -299,47 +540,297 @@ pub mod execute {
         USER_ADDRESSES.save(deps.storage, white_addr.clone(), &true)?;
         USER_ADDRESSES.save(deps.storage, black_addr.clone(), &true)?;
 
-        // Time control: ~2 days total per player (172,800 blocks at 1 block/second)
-        let initial_time = 172_800u64;
+        let config = CONFIG.load(deps.storage)?;
+        validate_time_control(&time_control, clock_unit, &config)?;
+        let initial_time = time_control.initial_clock();
+        let category = query::rating_category(&time_control, clock_unit, config.avg_block_time_ms);
         
         let game = ChessGame {
-            id: game_id.clone(),
-            white: info.sender.clone(),
-            black: opponent.clone(),
-            moves: String::new(),
+            id: game_id,
+            white,
+            black,
             current_fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string(),
-            status: "active".to_string(),
-            current_turn: "white".to_string(),
+            status: GameState::Active,
+            current_turn: Color::White,
             last_move_block: env.block.height,
+            last_move_time: env.block.time.seconds(),
             white_time_remaining: initial_time,
             black_time_remaining: initial_time,
             created_block: env.block.height,
             claim_block: None,
             time_control,
+            clock_unit,
+            category,
+            rated,
+            wager,
             move_count: 0,
             draw_proposed_by: None,
         };
 
-        GAMES.save(deps.storage, game_id.clone(), &game)?;
-        GAME_IDS.save(deps.storage, game_id.clone(), &true)?;
+        GAMES.save(deps.storage, game_id, &game)?;
+        record_position(deps.storage, game_id, &Chess::default())?;
+
+        Ok(game)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_challenge(
+        deps: DepsMut,
+        env: Env,
+        sender: Addr,
+        wager: Option<Wager>,
+        opponent: Option<String>,
+        color: ColorChoice,
+        time_control: TimeControl,
+        clock_unit: ClockUnit,
+        rated: bool,
+        color_commitment: Option<Binary>,
+    ) -> Result<Response, ContractError> {
+        let opponent = opponent.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+        if opponent.as_ref() == Some(&sender) {
+            return Err(ContractError::CannotChallengeSelf {});
+        }
+        // Random colors are settled by a secret committed to here and revealed once accepted
+        let commitment_valid = match (color, &color_commitment) {
+            (ColorChoice::Random, commitment) => commitment.as_ref().is_some_and(|commitment| commitment.len() == 32),
+            (_, commitment) => commitment.is_none(),
+        };
+        if !commitment_valid {
+            return Err(ContractError::InvalidColorCommitment {});
+        }
+
+        let config = CONFIG.load(deps.storage)?;
+        validate_time_control(&time_control, clock_unit, &config)?;
+
+        let id = CHALLENGE_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+        CHALLENGE_COUNT.save(deps.storage, &id)?;
+        let challenge = Challenge {
+            id,
+            challenger: sender.clone(),
+            opponent,
+            challenger_color: color,
+            time_control,
+            clock_unit,
+            rated,
+            wager,
+            created_block: env.block.height,
+            color_commitment,
+            pending_reveal: None,
+        };
+        CHALLENGES.save(deps.storage, id, &challenge)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "create_challenge")
+            .add_attribute("challenge_id", id.to_string())
+            .add_attribute("challenger", sender)
+            .add_attribute("opponent", challenge.opponent.map(|a| a.to_string()).unwrap_or_default())
+            .add_attribute("color", color.as_str())
+            .add_attribute("rated", rated.to_string())
+            .add_attribute("wager", challenge.wager.map(|stake| stake.to_string()).unwrap_or_default()))
+    }
+
+    pub fn accept_challenge(
+        deps: DepsMut,
+        env: Env,
+        sender: Addr,
+        wager: Option<Wager>,
+        challenge_id: u64,
+        entropy: Option<Binary>,
+    ) -> Result<Response, ContractError> {
+        let mut challenge = CHALLENGES.load(deps.storage, challenge_id)?;
+        if challenge.pending_reveal.is_some() {
+            return Err(ContractError::ChallengeAwaitsReveal {});
+        }
+        if challenge.challenger == sender {
+            return Err(ContractError::CannotAcceptOwnChallenge {});
+        }
+        if challenge.opponent.as_ref().is_some_and(|opponent| opponent != sender) {
+            return Err(ContractError::NotChallengeRecipient {});
+        }
+        if wager != challenge.wager {
+            return Err(ContractError::WagerNotMatched {
+                expected: challenge.wager.map(|stake| stake.to_string()).unwrap_or_else(|| "nothing".to_string()),
+            });
+        }
+
+        let challenger_color = match challenge.challenger_color {
+            ColorChoice::White => Color::White,
+            ColorChoice::Black => Color::Black,
+            ColorChoice::Random => {
+                // The escrow stays with the challenge until the challenger reveals their secret
+                let entropy = entropy.ok_or(ContractError::EntropyRequired {})?;
+                let deadline = env.block.height + COLOR_REVEAL_BLOCKS;
+                challenge.pending_reveal = Some(PendingReveal { opponent: sender.clone(), entropy, deadline });
+                CHALLENGES.save(deps.storage, challenge_id, &challenge)?;
+                return Ok(Response::new()
+                    .add_attribute("action", "accept_challenge")
+                    .add_attribute("challenge_id", challenge_id.to_string())
+                    .add_attribute("opponent", sender)
+                    .add_attribute("reveal_deadline", deadline.to_string()));
+            }
+        };
+        CHALLENGES.remove(deps.storage, challenge_id);
+        start_challenge_game(deps, &env, challenge, sender, challenger_color, "accept_challenge")
+    }
+
+    /// Settle the colors of an accepted random color challenge with the challenger's secret
+    pub fn reveal_color(
+        deps: DepsMut,
+        env: Env,
+        challenge_id: u64,
+        secret: Binary,
+    ) -> Result<Response, ContractError> {
+        let mut challenge = CHALLENGES.load(deps.storage, challenge_id)?;
+        let pending = challenge.pending_reveal.take().ok_or(ContractError::NoPendingReveal {})?;
+        if env.block.height > pending.deadline {
+            return Err(ContractError::RevealDeadlinePassed {});
+        }
+        let digest = Sha256::digest(secret.as_slice());
+        if challenge.color_commitment.as_ref().is_none_or(|commitment| commitment.as_slice() != digest.as_slice()) {
+            return Err(ContractError::InvalidReveal {});
+        }
+        CHALLENGES.remove(deps.storage, challenge_id);
+
+        let challenger_color = random_color(&secret, &pending.entropy);
+        start_challenge_game(deps, &env, challenge, pending.opponent, challenger_color, "reveal_color")
+    }
+
+    /// Hand the whole escrow of an accepted random color challenge to the opponent once the
+    /// challenger missed the reveal deadline
+    pub fn claim_reveal_timeout(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        challenge_id: u64,
+    ) -> Result<Response, ContractError> {
+        let challenge = CHALLENGES.load(deps.storage, challenge_id)?;
+        let pending = challenge.pending_reveal.as_ref().ok_or(ContractError::NoPendingReveal {})?;
+        if pending.opponent != info.sender {
+            return Err(ContractError::NotChallengeRecipient {});
+        }
+        if env.block.height <= pending.deadline {
+            return Err(ContractError::RevealDeadlineNotReached { deadline: pending.deadline });
+        }
+        CHALLENGES.remove(deps.storage, challenge_id);
+
+        let payout = challenge.wager.iter()
+            .map(|stake| send_stake(stake, &info.sender, stake.amount() + stake.amount()))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(Response::new()
+            .add_messages(payout)
+            .add_attribute("action", "claim_reveal_timeout")
+            .add_attribute("challenge_id", challenge_id.to_string())
+            .add_attribute("player", info.sender))
+    }
+
+    /// Start the game of a challenge taken by `opponent`, with the challenger playing
+    /// `challenger_color`
+    fn start_challenge_game(
+        deps: DepsMut,
+        env: &Env,
+        challenge: Challenge,
+        opponent: Addr,
+        challenger_color: Color,
+        action: &str,
+    ) -> Result<Response, ContractError> {
+        let (white, black) = match challenger_color {
+            Color::White => (challenge.challenger, opponent),
+            Color::Black => (opponent, challenge.challenger),
+        };
+        let game = start_game(deps, env, white, black, challenge.time_control, challenge.clock_unit, challenge.rated, challenge.wager)?;
+
+        Ok(Response::new()
+            .set_data(to_json_binary(&GameCreatedResponse { game_id: game.id })?)
+            .add_attribute("action", action)
+            .add_attribute("challenge_id", challenge.id.to_string())
+            .add_attribute("game_id", game.id.to_string())
+            .add_attribute("color_choice", challenge.challenger_color.as_str())
+            .add_attribute("challenger_color", challenger_color.as_str())
+            .add_attribute("rated", challenge.rated.to_string())
+            .add_attribute("white", game.white)
+            .add_attribute("black", game.black))
+    }
+
+    /// CW20 tokens sent to the contract, escrowed as the wager of the embedded challenge message
+    pub fn receive_cw20(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if !config.cw20_allowlist.contains(&info.sender) {
+            return Err(ContractError::Cw20NotAllowed { address: info.sender.to_string() });
+        }
+        if msg.amount.is_zero() {
+            return Err(ContractError::InvalidWager { reason: "stake must not be zero".to_string() });
+        }
+
+        let sender = deps.api.addr_validate(&msg.sender)?;
+        let wager = Some(Wager::Cw20(Cw20CoinVerified { address: info.sender, amount: msg.amount }));
+        // Anything but a wager fails so the token contract reverts the transfer
+        let hook = from_json(&msg.msg).map_err(|_| ContractError::InvalidReceiveMsg {})?;
+        match hook {
+            ReceiveMsg::CreateChallenge { opponent, color, time_control, clock_unit, rated, color_commitment } => {
+                create_challenge(deps, env, sender, wager, opponent, color, time_control, clock_unit.unwrap_or_default(), rated, color_commitment)
+            }
+            ReceiveMsg::AcceptChallenge { challenge_id, entropy } => {
+                accept_challenge(deps, env, sender, wager, challenge_id, entropy)
+            }
+        }
+    }
+
+    pub fn decline_challenge(
+        deps: DepsMut,
+        info: MessageInfo,
+        challenge_id: u64,
+    ) -> Result<Response, ContractError> {
+        let challenge = CHALLENGES.load(deps.storage, challenge_id)?;
+        if challenge.opponent.as_ref() != Some(&info.sender) {
+            return Err(ContractError::NotChallengeRecipient {});
+        }
+        if challenge.pending_reveal.is_some() {
+            return Err(ContractError::ChallengeAwaitsReveal {});
+        }
+        CHALLENGES.remove(deps.storage, challenge_id);
+
+        Ok(Response::new()
+            .add_messages(refund_challenge(&challenge)?)
+            .add_attribute("action", "decline_challenge")
+            .add_attribute("challenge_id", challenge_id.to_string())
+            .add_attribute("player", info.sender))
+    }
+
+    pub fn cancel_challenge(
+        deps: DepsMut,
+        info: MessageInfo,
+        challenge_id: u64,
+    ) -> Result<Response, ContractError> {
+        let challenge = CHALLENGES.load(deps.storage, challenge_id)?;
+        if challenge.challenger != info.sender {
+            return Err(ContractError::NotChallenger {});
+        }
+        if challenge.pending_reveal.is_some() {
+            return Err(ContractError::ChallengeAwaitsReveal {});
+        }
+        CHALLENGES.remove(deps.storage, challenge_id);
 
         Ok(Response::new()
-            .add_attribute("action", "create_game")
-            .add_attribute("game_id", game_id)
-            .add_attribute("white", info.sender)
-            .add_attribute("black", opponent))
+            .add_messages(refund_challenge(&challenge)?)
+            .add_attribute("action", "cancel_challenge")
+            .add_attribute("challenge_id", challenge_id.to_string()))
     }
 
     pub fn make_move(
         mut deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        game_id: String,
+        game_id: u64,
         from: String,
         to: String,
         promotion: Option<String>,
     ) -> Result<Response, ContractError> {
-        let mut game = GAMES.load(deps.storage, game_id.clone())?;
+        let mut game = GAMES.load(deps.storage, game_id)?;
 
         // Check if it's the player's turn
         let is_white = game.white == info.sender;
@@ -349,15 +840,19 @@ pub mod execute {
             return Err(ContractError::NotPlayerInGame {});
         }
 
-        if (is_white && game.current_turn != "white") || (is_black && game.current_turn != "black") {
+        if !game.status.is_active() {
+            return Err(ContractError::GameNotActive {});
+        }
+
+        if (is_white && game.current_turn != Color::White) || (is_black && game.current_turn != Color::Black) {
             return Err(ContractError::NotYourTurn {});
         }
 
-        // Time control logic with increments
+        // Time control logic
         // Only enforce time after both players have made their first move (move_count >= 2)
         if game.move_count >= 2 {
             // Calculate time used since last move
-            let time_used = env.block.height.saturating_sub(game.last_move_block);
+            let time_used = query::time_since_last_move(&game, &env.block);
             
             // Check if current player has enough time
             let current_time_remaining = if is_white {
@@ -366,27 +861,30 @@ pub mod execute {
                 game.black_time_remaining
             };
             
-            if time_used >= current_time_remaining {
-                // Player has run out of time - they lose
-                let white_won = !is_white;  // Opponent wins
-                let black_won = !is_black;  // Opponent wins
-                game.status = if is_white { "black_won" } else { "white_won" }.to_string();
-                
-                // Update ELO ratings for timeout loss
-                update_profiles_after_game(&mut deps, &game, white_won, black_won, false)?;
+            if game.time_control.has_expired(current_time_remaining, time_used) {
+                // Player has run out of time - opponent wins and ELO ratings are updated.
+                // The move is not played, but we return Ok so the result is committed
+                // instead of being reverted together with an error.
+                let outcome = GameState::won_by(game.current_turn.other(), Termination::Timeout);
+                finish_game(&mut deps, &env, &mut game, outcome)?;
                 
-                GAMES.save(deps.storage, game_id.clone(), &game)?;
+                GAMES.save(deps.storage, game_id, &game)?;
                 
-                return Err(ContractError::IllegalMove { 
-                    error: "Time expired - you have lost the game".to_string() 
-                });
+                return Ok(Response::new()
+                    .add_attribute("action", "make_move")
+                    .add_attribute("game_id", game_id.to_string())
+                    .add_attribute("player", info.sender)
+                    .add_attribute("move_rejected", "time_expired")
+                    .add_attributes(game_over_attributes(&game))
+            .add_messages(wager_payouts(deps.storage, &game)?));
             }
             
-            // Deduct time used from current player's clock
+            // Charge the time used and apply the increment or delay of the time control
+            let clock_after_move = game.time_control.clock_after_move(current_time_remaining, time_used);
             if is_white {
-                game.white_time_remaining = game.white_time_remaining.saturating_sub(time_used);
+                game.white_time_remaining = clock_after_move;
             } else {
-                game.black_time_remaining = game.black_time_remaining.saturating_sub(time_used);
+                game.black_time_remaining = clock_after_move;
             }
         }
 
@@ -399,168 +897,384 @@ pub mod execute {
             });
         }
 
-        // Update game state
-        let move_string = format!("{}{}{}", from, to, promotion.unwrap_or_default());
-        game.moves = if game.moves.is_empty() {
-            move_string
-        } else {
-            format!("{},{}", game.moves, move_string)
-        };
+        // Update game state, storing the normalized UCI so castling is always recorded as e1g1 etc.
+        let move_string = move_validation.uci.clone().unwrap_or_default();
+        let uci: UciMove = move_string.parse()
+            .map_err(|_| ContractError::InvalidMove { details: move_string.clone() })?;
+        push_move(deps.storage, game_id, game.move_count, pack_move(&uci)?)?;
         
-        if let Some(new_fen) = move_validation.resulting_fen {
+        if let Some(new_fen) = move_validation.resulting_fen.clone() {
             game.current_fen = new_fen.clone();
             
+            let occurrences = record_position(deps.storage, game_id, &query::parse_position(&new_fen)?)?;
+
             // Check for checkmate/stalemate/draw after the move
             let position_check = query::verify_position_internal(new_fen)?;
             match position_check.status {
                 GameStatus::Checkmate => {
                     // The current player (who just moved) wins by checkmate
-                    let white_won = game.current_turn == "white";
-                    let black_won = game.current_turn == "black";
-                    game.status = if white_won { "white_won" } else { "black_won" }.to_string();
-                    
-                    // Update ELO ratings
-                    update_profiles_after_game(&mut deps, &game, white_won, black_won, false)?;
+                    let outcome = GameState::won_by(game.current_turn, Termination::Checkmate);
+                    finish_game(&mut deps, &env, &mut game, outcome)?;
                 },
                 GameStatus::Stalemate => {
-                    game.status = "draw".to_string();
-                    
-                    // Update ELO ratings for draw
-                    update_profiles_after_game(&mut deps, &game, false, false, true)?;
+                    finish_game(&mut deps, &env, &mut game, GameState::Draw { reason: Termination::Stalemate })?;
                 },
                 GameStatus::Draw => {
-                    game.status = "draw".to_string();
-                    
-                    // Update ELO ratings for draw
-                    update_profiles_after_game(&mut deps, &game, false, false, true)?;
+                    finish_game(&mut deps, &env, &mut game, GameState::Draw { reason: Termination::InsufficientMaterial })?;
                 },
                 GameStatus::Active => {
-                    // Game continues
+                    // Fivefold repetition and the 75-move rule end the game without a claim (FIDE 9.6)
+                    if occurrences >= 5 {
+                        finish_game(&mut deps, &env, &mut game, GameState::Draw { reason: Termination::FivefoldRepetition })?;
+                    } else if query::parse_position(&game.current_fen)?.halfmoves() >= 150 {
+                        finish_game(&mut deps, &env, &mut game, GameState::Draw { reason: Termination::SeventyFiveMoveRule })?;
+                    }
                 }
             }
         }
         
-        // Add time increment after successful move
-        // First 20 moves: 10 minutes (600 blocks) increment
-        // After 20 moves: 1 minute (60 blocks) increment
-        if game.move_count >= 2 {  // Only add increment after both players have made first move
-            let increment = if game.move_count <= 20 {
-                600u64  // 10 minutes for moves 1-20
-            } else {
-                60u64   // 1 minute for moves 21+
-            };
-            
-            // Add increment to the player who just moved
-            if is_white {
-                game.white_time_remaining = game.white_time_remaining.saturating_add(increment);
-            } else {
-                game.black_time_remaining = game.black_time_remaining.saturating_add(increment);
-            }
-        }
-        
         // Update move count and last move block
         game.move_count += 1;
         game.last_move_block = env.block.height;
+        game.last_move_time = env.block.time.seconds();
+
+        let clock_remaining = match (game.time_control, game.current_turn) {
+            (TimeControl::Unlimited, _) => None,
+            (_, Color::White) => Some(game.white_time_remaining),
+            (_, Color::Black) => Some(game.black_time_remaining),
+        };
+        MOVE_HISTORY.save(deps.storage, (game_id, game.move_count), &MoveRecord {
+            ply: game.move_count,
+            san: move_validation.san.unwrap_or_default(),
+            fen: game.current_fen.clone(),
+            clock_remaining,
+            block_height: Some(env.block.height),
+            block_time: Some(env.block.time.seconds()),
+        })?;
         
         // Only switch turns if game is still active
-        if game.status == "active" {
-            game.current_turn = if game.current_turn == "white" { "black" } else { "white" }.to_string();
+        if game.status.is_active() {
+            game.current_turn = game.current_turn.other();
         }
 
-        GAMES.save(deps.storage, game_id.clone(), &game)?;
+        GAMES.save(deps.storage, game_id, &game)?;
 
-        Ok(Response::new()
+        let mut response = Response::new()
             .add_attribute("action", "make_move")
-            .add_attribute("game_id", game_id)
+            .add_attribute("game_id", game_id.to_string())
             .add_attribute("player", info.sender)
-            .add_attribute("move", format!("{}{}", from, to)))
+            .add_attribute("move", move_string);
+        if !game.status.is_active() {
+            response = response
+                .add_attributes(game_over_attributes(&game))
+                .add_messages(wager_payouts(deps.storage, &game)?);
+        }
+
+        Ok(response)
     }
 
-    pub fn update_game_status(
+    /// Claim a win because the opponent's clock has run out
+    pub fn claim_timeout(
         mut deps: DepsMut,
+        env: Env,
         info: MessageInfo,
-        game_id: String,
-        status: String,
+        game_id: u64,
     ) -> Result<Response, ContractError> {
-        let mut game = GAMES.load(deps.storage, game_id.clone())?;
+        let mut game = GAMES.load(deps.storage, game_id)?;
 
-        // Check if sender is a player in the game
-        if game.white != info.sender && game.black != info.sender {
-            return Err(ContractError::NotPlayerInGame {});
+        let claimant = player_color(&game, &info.sender)?;
+        if !game.status.is_active() {
+            return Err(ContractError::GameNotActive {});
         }
 
-        let old_status = game.status.clone();
-        game.status = status.clone();
-        
-        // Handle ELO updates for game-ending status changes
-        if old_status == "active" {
-            match status.as_str() {
-                "white_won" => {
-                    update_profiles_after_game(&mut deps, &game, true, false, false)?;
-                },
-                "black_won" => {
-                    update_profiles_after_game(&mut deps, &game, false, true, false)?;
-                },
-                "draw" => {
-                    update_profiles_after_game(&mut deps, &game, false, false, true)?;
-                },
-                _ => {
-                    // Other status changes don't affect ELO
-                }
-            }
+        // Only the waiting player can claim, against the player whose clock is running
+        if claimant == game.current_turn {
+            return Err(ContractError::CannotClaimOwnTimeout {});
+        }
+        if game.move_count < 2 {
+            return Err(ContractError::ClockNotStarted {});
+        }
+
+        if game.time_control == TimeControl::Unlimited {
+            return Err(ContractError::NoClock {});
+        }
+        if !query::time_expired(&game, &env.block) {
+            let (white_remaining, black_remaining) = query::clocks_at(&game, &env.block);
+            let remaining = match game.current_turn {
+                Color::White => white_remaining,
+                Color::Black => black_remaining,
+            };
+            return Err(ContractError::TimeNotExpired { remaining, unit: game.clock_unit });
         }
 
-        GAMES.save(deps.storage, game_id.clone(), &game)?;
+        finish_game(&mut deps, &env, &mut game, GameState::won_by(claimant, Termination::Timeout))?;
+        GAMES.save(deps.storage, game_id, &game)?;
 
         Ok(Response::new()
-            .add_attribute("action", "update_game_status")
-            .add_attribute("game_id", game_id)
-            .add_attribute("status", status)
-            .add_attribute("player", info.sender))
+            .add_attribute("action", "claim_timeout")
+            .add_attribute("game_id", game_id.to_string())
+            .add_attribute("claimed_by", info.sender)
+            .add_attributes(game_over_attributes(&game))
+            .add_messages(wager_payouts(deps.storage, &game)?))
     }
 
-    pub fn resign_game(
+    /// Claim a draw under the threefold repetition or fifty-move rule
+    pub fn claim_draw(
         mut deps: DepsMut,
+        env: Env,
         info: MessageInfo,
-        game_id: String,
+        game_id: u64,
+        reason: DrawClaim,
     ) -> Result<Response, ContractError> {
-        let mut game = GAMES.load(deps.storage, game_id.clone())?;
-
-        // Check if sender is a player in the game
-        if game.white != info.sender && game.black != info.sender {
-            return Err(ContractError::NotPlayerInGame {});
-        }
+        let mut game = GAMES.load(deps.storage, game_id)?;
 
-        // Can only resign active games
-        if game.status != "active" {
+        player_color(&game, &info.sender)?;
+        if !game.status.is_active() {
             return Err(ContractError::GameNotActive {});
         }
 
-        // Determine winner based on who resigned
-        let is_white = game.white == info.sender;
-        let white_won = !is_white;  // Opponent wins
-        let black_won = is_white;   // Opponent wins
-        
-        game.status = if is_white { "black_won" } else { "white_won" }.to_string();
-        
-        // Update ELO ratings for resignation
-        update_profiles_after_game(&mut deps, &game, white_won, black_won, false)?;
-
-        GAMES.save(deps.storage, game_id.clone(), &game)?;
+        let termination = match reason {
+            DrawClaim::ThreefoldRepetition => {
+                let occurrences = query::count_repetitions(deps.as_ref(), &game)?;
+                if occurrences < 3 {
+                    return Err(ContractError::NoThreefoldRepetition { occurrences });
+                }
+                Termination::ThreefoldRepetition
+            }
+            DrawClaim::FiftyMoveRule => {
+                let pos = query::parse_position(&game.current_fen)?;
+                if pos.halfmoves() < 100 {
+                    return Err(ContractError::FiftyMoveRuleNotReached { halfmoves: pos.halfmoves() });
+                }
+                Termination::FiftyMoveRule
+            }
+        };
+
+        finish_game(&mut deps, &env, &mut game, GameState::Draw { reason: termination })?;
+        GAMES.save(deps.storage, game_id, &game)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "claim_draw")
+            .add_attribute("game_id", game_id.to_string())
+            .add_attribute("claimed_by", info.sender)
+            .add_attributes(game_over_attributes(&game))
+            .add_messages(wager_payouts(deps.storage, &game)?))
+    }
+
+    /// Abort a game before both players have made their first move. Aborted games are unrated.
+    pub fn abort_game(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        game_id: u64,
+    ) -> Result<Response, ContractError> {
+        let mut game = GAMES.load(deps.storage, game_id)?;
+
+        player_color(&game, &info.sender)?;
+        if !game.status.is_active() {
+            return Err(ContractError::GameNotActive {});
+        }
+        if game.move_count >= 2 {
+            return Err(ContractError::AbortNotAllowed {});
+        }
+
+        finish_game(&mut deps, &env, &mut game, GameState::Aborted)?;
+        GAMES.save(deps.storage, game_id, &game)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "abort_game")
+            .add_attribute("game_id", game_id.to_string())
+            .add_attribute("aborted_by", info.sender)
+            .add_attributes(game_over_attributes(&game))
+            .add_messages(wager_payouts(deps.storage, &game)?))
+    }
+
+    /// Admin arbiter decides the result of a disputed game
+    pub fn adjudicate_game(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        game_id: u64,
+        result: GameState,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let mut game = GAMES.load(deps.storage, game_id)?;
+        if !game.status.is_active() {
+            return Err(ContractError::GameNotActive {});
+        }
+        if result.is_active() {
+            return Err(ContractError::InvalidAdjudication {});
+        }
+
+        finish_game(&mut deps, &env, &mut game, result)?;
+        GAMES.save(deps.storage, game_id, &game)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "adjudicate_game")
+            .add_attribute("game_id", game_id.to_string())
+            .add_attribute("arbiter", info.sender)
+            .add_attributes(game_over_attributes(&game))
+            .add_messages(wager_payouts(deps.storage, &game)?))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        admin: Option<String>,
+        time_control_bounds: Option<TimeControlBounds>,
+        avg_block_time_ms: Option<u64>,
+        rating_system: Option<RatingSystem>,
+        house_fee_bps: Option<u16>,
+        treasury: Option<String>,
+        clear_treasury: bool,
+        cw20_allowlist: Option<Vec<String>>,
+        nft_contract: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if let Some(admin) = admin {
+            config.admin = deps.api.addr_validate(&admin)?;
+        }
+        if let Some(bounds) = time_control_bounds {
+            config.time_control_bounds = bounds;
+        }
+        if let Some(avg_block_time_ms) = avg_block_time_ms {
+            config.avg_block_time_ms = avg_block_time_ms;
+        }
+        if let Some(rating_system) = rating_system {
+            config.rating_system = rating_system;
+        }
+        if let Some(house_fee_bps) = house_fee_bps {
+            config.house_fee_bps = house_fee_bps;
+        }
+        if let Some(treasury) = treasury {
+            config.treasury = Some(deps.api.addr_validate(&treasury)?);
+        }
+        if clear_treasury {
+            config.treasury = None;
+        }
+        if let Some(cw20_allowlist) = cw20_allowlist {
+            config.cw20_allowlist = cw20_allowlist.iter()
+                .map(|address| deps.api.addr_validate(address))
+                .collect::<StdResult<_>>()?;
+        }
+        if let Some(nft_contract) = nft_contract {
+            config.nft_contract = Some(deps.api.addr_validate(&nft_contract)?);
+        }
+        validate_house_fee(&config)?;
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_config")
+            .add_attribute("admin", config.admin))
+    }
+
+    pub fn resign_game(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        game_id: u64,
+    ) -> Result<Response, ContractError> {
+        let mut game = GAMES.load(deps.storage, game_id)?;
+
+        // Check if sender is a player in the game
+        if game.white != info.sender && game.black != info.sender {
+            return Err(ContractError::NotPlayerInGame {});
+        }
+
+        // Can only resign active games
+        if !game.status.is_active() {
+            return Err(ContractError::GameNotActive {});
+        }
+
+        // Determine winner based on who resigned
+        let is_white = game.white == info.sender;
+        let winner = if is_white { Color::Black } else { Color::White };
+        
+        // Opponent wins, update ELO ratings for resignation
+        finish_game(&mut deps, &env, &mut game, GameState::won_by(winner, Termination::Resignation))?;
+
+        GAMES.save(deps.storage, game_id, &game)?;
 
         Ok(Response::new()
             .add_attribute("action", "resign_game")
-            .add_attribute("game_id", game_id)
+            .add_attribute("game_id", game_id.to_string())
             .add_attribute("resigned_player", info.sender)
-            .add_attribute("winner", if is_white { game.black.to_string() } else { game.white.to_string() }))
+            .add_attributes(game_over_attributes(&game))
+            .add_messages(wager_payouts(deps.storage, &game)?))
+    }
+
+    /// Mint a finished game as an NFT owned by the calling player, with the game record as
+    /// on-chain metadata. Every game can be minted once.
+    pub fn mint_game_nft(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        game_id: u64,
+    ) -> Result<Response, ContractError> {
+        let game = GAMES.load(deps.storage, game_id)?;
+        if game.white != info.sender && game.black != info.sender {
+            return Err(ContractError::NotPlayerInGame {});
+        }
+        if matches!(game.status, GameState::Active | GameState::Aborted) {
+            return Err(ContractError::GameNotFinished {});
+        }
+        if GAME_NFTS.has(deps.storage, game_id) {
+            return Err(ContractError::GameAlreadyMinted {});
+        }
+        let nft_contract = CONFIG.load(deps.storage)?.nft_contract
+            .ok_or(ContractError::NftContractNotSet {})?;
+        GAME_NFTS.save(deps.storage, game_id, &info.sender)?;
+
+        let white = query::username(deps.storage, &game.white)?;
+        let black = query::username(deps.storage, &game.black)?;
+        let result = query::pgn_result(&game.status);
+        let attribute = |trait_type: &str, value: String| Trait { display_type: None, trait_type: trait_type.to_string(), value };
+        let metadata = NftExtensionMsg {
+            name: Some(format!("Game #{}: {} vs {}", game.id, white, black)),
+            description: Some(format!("{} vs {}, {} by {}", white, black, result, game.status.termination().unwrap_or(Termination::Unknown))),
+            attributes: Some(vec![
+                attribute("white", game.white.to_string()),
+                attribute("black", game.black.to_string()),
+                attribute("result", result.to_string()),
+                attribute("termination", game.status.termination().unwrap_or(Termination::Unknown).as_str().to_string()),
+                attribute("final_fen", game.current_fen.clone()),
+                attribute("pgn", query::game_pgn(deps.storage, &env.block, &game)?),
+            ]),
+            ..NftExtensionMsg::default()
+        };
+        let mint: Cw721ExecuteMsg<Option<NftExtensionMsg>, Option<Empty>, Empty> = Cw721ExecuteMsg::Mint {
+            token_id: game_id.to_string(),
+            owner: info.sender.to_string(),
+            token_uri: None,
+            extension: Some(metadata),
+        };
+
+        Ok(Response::new()
+            .add_message(WasmMsg::Execute {
+                contract_addr: nft_contract.to_string(),
+                msg: to_json_binary(&mint)?,
+                funds: vec![],
+            })
+            .add_attribute("action", "mint_game_nft")
+            .add_attribute("game_id", game_id.to_string())
+            .add_attribute("owner", info.sender))
     }
 
     pub fn propose_draw(
         deps: DepsMut,
         info: MessageInfo,
-        game_id: String,
+        game_id: u64,
     ) -> Result<Response, ContractError> {
-        let mut game = GAMES.load(deps.storage, game_id.clone())?;
+        let mut game = GAMES.load(deps.storage, game_id)?;
 
         // Check if sender is a player in the game
         if game.white != info.sender && game.black != info.sender {
@@ -568,7 +1282,7 @@ pub mod execute {
         }
 
         // Can only propose draw in active games
-        if game.status != "active" {
+        if !game.status.is_active() {
             return Err(ContractError::GameNotActive {});
         }
 
@@ -580,21 +1294,22 @@ pub mod execute {
         }
 
         game.draw_proposed_by = Some(info.sender.to_string());
-        GAMES.save(deps.storage, game_id.clone(), &game)?;
+        GAMES.save(deps.storage, game_id, &game)?;
 
         Ok(Response::new()
             .add_attribute("action", "propose_draw")
-            .add_attribute("game_id", game_id)
+            .add_attribute("game_id", game_id.to_string())
             .add_attribute("proposed_by", info.sender))
     }
 
     pub fn respond_to_draw(
         mut deps: DepsMut,
+        env: Env,
         info: MessageInfo,
-        game_id: String,
+        game_id: u64,
         accept: bool,
     ) -> Result<Response, ContractError> {
-        let mut game = GAMES.load(deps.storage, game_id.clone())?;
+        let mut game = GAMES.load(deps.storage, game_id)?;
 
         // Check if sender is a player in the game
         if game.white != info.sender && game.black != info.sender {
@@ -602,7 +1317,7 @@ pub mod execute {
         }
 
         // Can only respond to draw proposals in active games
-        if game.status != "active" {
+        if !game.status.is_active() {
             return Err(ContractError::GameNotActive {});
         }
 
@@ -616,33 +1331,182 @@ pub mod execute {
         }
 
         if accept {
-            // Accept draw - game ends in draw
-            game.status = "draw".to_string();
-            game.draw_proposed_by = None;
-            
-            // Update ELO ratings for draw
-            update_profiles_after_game(&mut deps, &game, false, false, true)?;
+            // Accept draw - game ends in draw and ELO ratings are updated
+            finish_game(&mut deps, &env, &mut game, GameState::Draw { reason: Termination::Agreement })?;
             
-            GAMES.save(deps.storage, game_id.clone(), &game)?;
+            GAMES.save(deps.storage, game_id, &game)?;
 
             Ok(Response::new()
                 .add_attribute("action", "accept_draw")
-                .add_attribute("game_id", game_id)
+                .add_attribute("game_id", game_id.to_string())
                 .add_attribute("accepted_by", info.sender)
-                .add_attribute("result", "draw"))
+                .add_attributes(game_over_attributes(&game))
+            .add_messages(wager_payouts(deps.storage, &game)?))
         } else {
             // Decline draw - clear proposal and continue game
             game.draw_proposed_by = None;
-            GAMES.save(deps.storage, game_id.clone(), &game)?;
+            GAMES.save(deps.storage, game_id, &game)?;
 
             Ok(Response::new()
                 .add_attribute("action", "decline_draw")
-                .add_attribute("game_id", game_id)
+                .add_attribute("game_id", game_id.to_string())
                 .add_attribute("declined_by", info.sender))
         }
     }
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // Contracts instantiated before the config existed need an admin supplied here
+    let mut config = match (CONFIG.may_load(deps.storage)?, &msg.admin) {
+        (Some(config), _) => config,
+        (None, Some(admin)) => Config {
+            admin: deps.api.addr_validate(admin)?,
+            time_control_bounds: TimeControlBounds::default(),
+            avg_block_time_ms: default_avg_block_time_ms(),
+            rating_system: RatingSystem::default(),
+            house_fee_bps: 0,
+            treasury: None,
+            cw20_allowlist: vec![],
+            nft_contract: None,
+        },
+        (None, None) => return Err(ContractError::AdminRequired {}),
+    };
+    if let Some(admin) = msg.admin {
+        config.admin = deps.api.addr_validate(&admin)?;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    let migrated_games = migrate::convert_legacy_games(deps.branch(), &env.block)?;
+    let backfilled_positions = migrate::backfill_position_counts(deps.branch())?;
+    let backfilled_move_histories = migrate::backfill_move_history(deps.branch())?;
+    let migrated_profiles = migrate::convert_legacy_profiles(deps)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("migrated_games", migrated_games.to_string())
+        .add_attribute("backfilled_positions", backfilled_positions.to_string())
+        .add_attribute("migrated_profiles", migrated_profiles.to_string())
+        .add_attribute("backfilled_move_histories", backfilled_move_histories.to_string()))
+}
+
+pub mod migrate {
+    use super::*;
+
+    /// Move games stored under client supplied ids with string status/turn fields to
+    /// contract assigned ids and the typed layout. The old id stays resolvable through
+    /// `LEGACY_GAME_IDS`. Converted games are removed from the old namespace, so this is safe
+    /// to rerun. Saving them through `GAMES` writes their player and status index entries.
+    pub fn convert_legacy_games(deps: DepsMut, block: &BlockInfo) -> Result<u32, ContractError> {
+        let avg_block_time_ms = CONFIG.load(deps.storage)?.avg_block_time_ms;
+        let legacy_games = LEGACY_GAMES
+            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let mut game_count = GAME_COUNT.may_load(deps.storage)?.unwrap_or_default();
+        for (legacy_id, legacy_game) in &legacy_games {
+            game_count += 1;
+            // Only heights were recorded, estimate the block time of the last move from them
+            let blocks_since_last_move = block.height.saturating_sub(legacy_game.last_move_block);
+            let last_move_time = block.time.seconds()
+                .saturating_sub(query::to_seconds(blocks_since_last_move, ClockUnit::Blocks, avg_block_time_ms));
+            GAMES.save(deps.storage, game_count, &legacy_game.clone().into_game(game_count, last_move_time))?;
+            // Moves were a comma separated string, they are packed apart from the game now
+            for (ply, uci) in legacy_game.moves.split(',').filter(|m| !m.is_empty()).enumerate() {
+                let uci: UciMove = uci.to_lowercase().parse()
+                    .map_err(|_| cosmwasm_std::StdError::generic_err(format!("Invalid stored move: {}", uci)))?;
+                push_move(deps.storage, game_count, ply as u32, pack_move(&uci)?)?;
+            }
+            LEGACY_GAME_IDS.save(deps.storage, legacy_id.clone(), &game_count)?;
+            LEGACY_GAMES.remove(deps.storage, legacy_id.clone());
+            LEGACY_GAME_INDEX.remove(deps.storage, legacy_id.clone());
+        }
+        GAME_COUNT.save(deps.storage, &game_count)?;
+
+        Ok(legacy_games.len() as u32)
+    }
+
+    /// Rewrite profiles stored in the pre-migration layout, mapping current games to the new
+    /// ids and adding them to the rating index
+    pub fn convert_legacy_profiles(deps: DepsMut) -> Result<u32, ContractError> {
+        let addresses = USER_ADDRESSES
+            .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let mut migrated = 0u32;
+        for address in addresses {
+            if USER_PROFILES.may_load(deps.storage, address.clone()).is_ok() {
+                continue;
+            }
+            let legacy = LEGACY_USER_PROFILES.load(deps.storage, address.clone())?;
+            let profile = legacy.into_profile(|id| LEGACY_GAME_IDS.may_load(deps.storage, id.to_string()).ok().flatten());
+            // The old value can't be read as a `UserProfile`, so no stale index entries are removed
+            USER_PROFILES.replace(deps.storage, address, Some(&profile), None)?;
+            migrated += 1;
+        }
+
+        Ok(migrated)
+    }
+
+    /// Build repetition counts for active games created before positions were tracked
+    pub fn backfill_position_counts(deps: DepsMut) -> Result<u32, ContractError> {
+        let games: Vec<ChessGame> = GAMES
+            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .map(|item| item.map(|(_, game)| game))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let mut backfilled = 0u32;
+        for game in games.iter().filter(|game| game.status.is_active()) {
+            if query::count_repetitions(deps.as_ref(), game)? > 0 {
+                continue;
+            }
+            for hash in query::replay_position_hashes(deps.storage, game)? {
+                let key = (game.id, hash);
+                let occurrences = POSITION_COUNTS.may_load(deps.storage, key)?.unwrap_or_default();
+                POSITION_COUNTS.save(deps.storage, key, &(occurrences + 1))?;
+            }
+            backfilled += 1;
+        }
+
+        Ok(backfilled)
+    }
+
+    /// Replay games played before moves were recorded individually into `MOVE_HISTORY`.
+    /// Clocks and blocks of those moves are unknown and left empty.
+    pub fn backfill_move_history(deps: DepsMut) -> Result<u32, ContractError> {
+        let games: Vec<ChessGame> = GAMES
+            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .map(|item| item.map(|(_, game)| game))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let mut backfilled = 0u32;
+        for game in games.iter().filter(|game| game.move_count > 0) {
+            if MOVE_HISTORY.has(deps.storage, (game.id, 1)) {
+                continue;
+            }
+            let mut pos = Chess::default();
+            for (index, uci) in load_moves(deps.storage, game.id)?.iter().enumerate() {
+                let chess_move = query::parse_stored_move(&pos, uci)?;
+                let san = SanPlus::from_move_and_play_unchecked(&mut pos, &chess_move);
+                let ply = index as u32 + 1;
+                MOVE_HISTORY.save(deps.storage, (game.id, ply), &MoveRecord {
+                    ply,
+                    san: san.to_string(),
+                    fen: Fen::from_position(pos.clone(), shakmaty::EnPassantMode::Legal).to_string(),
+                    clock_remaining: None,
+                    block_height: None,
+                    block_time: None,
+                })?;
+            }
+            backfilled += 1;
+        }
+
+        Ok(backfilled)
+    }
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -655,11 +1519,23 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetGame { game_id } => {
             to_json_binary(&query::get_game(deps, game_id)?)
         }
-        QueryMsg::GetPlayerGames { player } => {
-            to_json_binary(&query::get_player_games(deps, player)?)
+        QueryMsg::GetGamePgn { game_id } => {
+            to_json_binary(&query::get_game_pgn(deps, env, game_id)?)
         }
-        QueryMsg::GetAllGameIds {} => {
-            to_json_binary(&query::get_all_game_ids(deps)?)
+        QueryMsg::GetMoveHistory { game_id, start_after, limit } => {
+            to_json_binary(&query::get_move_history(deps, game_id, start_after, limit)?)
+        }
+        QueryMsg::GetGameByLegacyId { legacy_id } => {
+            to_json_binary(&query::get_game_by_legacy_id(deps, legacy_id)?)
+        }
+        QueryMsg::GetPlayerGames { player, filter, start_after, limit, order } => {
+            to_json_binary(&query::get_player_games(deps, player, filter.unwrap_or_default(), start_after, limit, order.unwrap_or_default())?)
+        }
+        QueryMsg::ListGames { filter, start_after, limit, order } => {
+            to_json_binary(&query::list_games(deps, filter.unwrap_or_default(), start_after, limit, order.unwrap_or_default())?)
+        }
+        QueryMsg::GetAllGameIds { start_after, limit, order } => {
+            to_json_binary(&query::get_all_game_ids(deps, start_after, limit, order.unwrap_or_default())?)
         }
         QueryMsg::CheckTimeStatus { game_id } => {
             to_json_binary(&query::check_time_status(deps, env, game_id)?)
@@ -667,24 +1543,43 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetUserProfile { address } => {
             to_json_binary(&query::get_user_profile(deps, address)?)
         }
-        QueryMsg::GetAllUsers {} => {
-            to_json_binary(&query::get_all_users(deps)?)
+        QueryMsg::GetRatingHistory { player, start_after, limit, order } => {
+            to_json_binary(&query::get_rating_history(deps, player, start_after, limit, order.unwrap_or_default())?)
+        }
+        QueryMsg::Leaderboard { start_after, limit, include_provisional, active_since, category } => {
+            to_json_binary(&query::leaderboard(deps, start_after, limit, include_provisional.unwrap_or(false), active_since, category)?)
+        }
+        QueryMsg::GetAllUsers { start_after, limit, order } => {
+            to_json_binary(&query::get_all_users(deps, start_after, limit, order.unwrap_or_default())?)
+        }
+        QueryMsg::GetChallenge { challenge_id } => {
+            to_json_binary(&query::get_challenge(deps, challenge_id)?)
+        }
+        QueryMsg::ListOpenChallenges { player, start_after, limit, order } => {
+            to_json_binary(&query::list_open_challenges(deps, player, start_after, limit, order.unwrap_or_default())?)
+        }
+        QueryMsg::GetConfig {} => {
+            to_json_binary(&query::get_config(deps)?)
         }
     }
 }
 
 pub mod query {
     use super::*;
-    use cosmwasm_std::Addr;
+    use cosmwasm_std::Order;
+    use cw_storage_plus::{Bound, PrimaryKey};
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+    /// Games a filtered listing looks at before returning, matching or not
+    pub const MAX_GAME_SCAN: usize = 300;
+    /// Players a leaderboard page looks at before returning, listed or not
+    pub const MAX_PLAYER_SCAN: usize = 300;
     use shakmaty::san::SanPlus;
 
     pub fn verify_position_internal(fen: String) -> StdResult<VerificationResponse> {
         // Parse FEN into chess position
-        let fen_parsed: Fen = fen.parse()
-            .map_err(|_| cosmwasm_std::StdError::generic_err("Invalid FEN format"))?;
-        
-        let pos: Chess = fen_parsed.into_position(shakmaty::CastlingMode::Standard)
-            .map_err(|_| cosmwasm_std::StdError::generic_err("Invalid chess position"))?;
+        let pos = parse_position(&fen)?;
 
         // Check if king is in check
         let is_check = pos.checkers().any();
@@ -714,169 +1609,700 @@ pub mod query {
         })
     }
 
-    pub fn validate_move(
-        current_fen: String,
-        move_from: String,
-        move_to: String,
-        promotion: Option<String>,
-    ) -> StdResult<MoveValidationResponse> {
-        // Parse current position
-        let fen_parsed: Fen = current_fen.parse()
+    /// Parse a FEN string into a standard chess position
+    pub fn parse_position(fen: &str) -> StdResult<Chess> {
+        let fen_parsed: Fen = fen.parse()
             .map_err(|_| cosmwasm_std::StdError::generic_err("Invalid FEN format"))?;
-        
-        let mut pos: Chess = fen_parsed.into_position(shakmaty::CastlingMode::Standard)
-            .map_err(|_| cosmwasm_std::StdError::generic_err("Invalid chess position"))?;
 
+        fen_parsed.into_position(shakmaty::CastlingMode::Standard)
+            .map_err(|_| cosmwasm_std::StdError::generic_err("Invalid chess position"))
+    }
+
+    /// Resolve a UCI move (`e2e4`, `e1g1`, `e1h1`, `e5d6`, `a7a8q`) against the legal moves
+    /// of a position. Castling is accepted both as king-to-target (`e1g1`) and as
+    /// king-to-rook (`e1h1`, Chess960 style).
+    pub fn resolve_uci_move(pos: &Chess, uci: &UciMove) -> Option<Move> {
+        pos.legal_moves()
+            .into_iter()
+            .find(|m| &UciMove::from_standard(m) == uci || &UciMove::from_chess960(m) == uci)
+    }
+
+    /// Resolve a move given as from/to squares plus an optional promotion piece.
+    /// Returns the legal move or a human readable reason why it was rejected.
+    pub fn resolve_move(
+        pos: &Chess,
+        move_from: &str,
+        move_to: &str,
+        promotion: Option<&str>,
+    ) -> StdResult<Result<Move, String>> {
         // Parse squares
-        let from_square = move_from.parse()
+        let from_square: Square = move_from.parse()
             .map_err(|_| cosmwasm_std::StdError::generic_err("Invalid from square"))?;
-        let to_square = move_to.parse()
+        let to_square: Square = move_to.parse()
             .map_err(|_| cosmwasm_std::StdError::generic_err("Invalid to square"))?;
 
         // Parse promotion piece if provided
-        let promotion_role = if let Some(promo) = promotion {
-            match promo.to_lowercase().as_str() {
+        let promotion_role = match promotion.map(|p| p.to_lowercase()) {
+            Some(promo) => match promo.as_str() {
                 "q" => Some(Role::Queen),
                 "r" => Some(Role::Rook),
                 "b" => Some(Role::Bishop),
                 "n" => Some(Role::Knight),
-                _ => return Ok(MoveValidationResponse {
-                    is_valid: false,
-                    resulting_fen: None,
-                    error: Some("Invalid promotion piece".to_string()),
-                }),
-            }
-        } else {
-            None
+                _ => return Ok(Err("Invalid promotion piece".to_string())),
+            },
+            None => None,
         };
 
-        // Get the piece info from current position
-        let piece = pos.board().piece_at(from_square);
-        let capture_role = pos.board().piece_at(to_square).map(|p| p.role);
-        
-        if piece.is_none() {
-            return Ok(MoveValidationResponse {
-                is_valid: false,
-                resulting_fen: None,
-                error: Some("No piece at from square".to_string()),
-            });
+        if pos.board().piece_at(from_square).is_none() {
+            return Ok(Err("No piece at from square".to_string()));
         }
 
-        // Create move
-        let chess_move = Move::Normal {
+        let uci = UciMove::Normal {
             from: from_square,
             to: to_square,
-            capture: capture_role,
             promotion: promotion_role,
-            role: piece.unwrap().role,
         };
 
-        // Check if move is legal (follows all chess rules)
-        if pos.is_legal(&chess_move) {
-            // Make the move and get resulting FEN
-            pos.play_unchecked(&chess_move);
-            let resulting_fen = Fen::from_position(pos, shakmaty::EnPassantMode::Legal).to_string();
+        if let Some(legal_move) = resolve_uci_move(pos, &uci) {
+            return Ok(Ok(legal_move));
+        }
 
-            Ok(MoveValidationResponse {
-                is_valid: true,
-                resulting_fen: Some(resulting_fen),
-                error: None,
-            })
-        } else {
-            Ok(MoveValidationResponse {
+        // Give a clearer error when a pawn reaches the last rank without a promotion piece
+        if promotion_role.is_none() {
+            let as_queen = UciMove::Normal {
+                from: from_square,
+                to: to_square,
+                promotion: Some(Role::Queen),
+            };
+            if resolve_uci_move(pos, &as_queen).is_some() {
+                return Ok(Err("Promotion piece required".to_string()));
+            }
+        }
+
+        Ok(Err("Illegal move".to_string()))
+    }
+
+    pub fn validate_move(
+        current_fen: String,
+        move_from: String,
+        move_to: String,
+        promotion: Option<String>,
+    ) -> StdResult<MoveValidationResponse> {
+        // Parse current position
+        let mut pos = parse_position(&current_fen)?;
+
+        match resolve_move(&pos, &move_from, &move_to, promotion.as_deref())? {
+            Ok(chess_move) => {
+                // Make the move and get resulting FEN
+                let san = SanPlus::from_move_and_play_unchecked(&mut pos, &chess_move);
+                let resulting_fen = Fen::from_position(pos, shakmaty::EnPassantMode::Legal).to_string();
+
+                Ok(MoveValidationResponse {
+                    is_valid: true,
+                    resulting_fen: Some(resulting_fen),
+                    uci: Some(UciMove::from_standard(&chess_move).to_string()),
+                    san: Some(san.to_string()),
+                    error: None,
+                })
+            }
+            Err(error) => Ok(MoveValidationResponse {
                 is_valid: false,
                 resulting_fen: None,
-                error: Some("Illegal move".to_string()),
-            })
+                uci: None,
+                san: None,
+                error: Some(error),
+            }),
         }
     }
 
-    pub fn get_game(deps: Deps, game_id: String) -> StdResult<GameResponse> {
+    pub fn get_game(deps: Deps, game_id: u64) -> StdResult<GameResponse> {
         let game = GAMES.may_load(deps.storage, game_id)?;
         Ok(GameResponse { game })
     }
 
-    pub fn get_player_games(deps: Deps, player: cosmwasm_std::Addr) -> StdResult<GamesResponse> {
-        let all_game_ids: Vec<String> = GAME_IDS
-            .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-            .collect::<StdResult<Vec<_>>>()?;
+    pub fn get_game_by_legacy_id(deps: Deps, legacy_id: String) -> StdResult<GameResponse> {
+        match LEGACY_GAME_IDS.may_load(deps.storage, legacy_id)? {
+            Some(game_id) => get_game(deps, game_id),
+            None => Ok(GameResponse { game: None }),
+        }
+    }
 
-        let mut player_games = Vec::new();
-        
-        for game_id in all_game_ids {
-            if let Some(game) = GAMES.may_load(deps.storage, game_id)? {
-                if game.white == player || game.black == player {
-                    player_games.push(game);
+    /// Range bounds that continue after `start_after` in the direction of `order`
+    fn page_bounds<'a, K: PrimaryKey<'a>>(start_after: Option<K>, order: Order) -> (Option<Bound<'a, K>>, Option<Bound<'a, K>>) {
+        let bound = start_after.map(Bound::exclusive);
+        match order {
+            Order::Ascending => (bound, None),
+            Order::Descending => (None, bound),
+        }
+    }
+
+    fn page_limit(limit: Option<u32>) -> usize {
+        limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize
+    }
+
+    pub fn get_player_games(
+        deps: Deps,
+        player: Addr,
+        filter: GameFilter,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> StdResult<GamesResponse> {
+        let order = order.into();
+        let (min, max) = page_bounds(start_after, order);
+        let as_white = GAMES.idx.white.prefix(player.clone()).range(deps.storage, min.clone(), max.clone(), order);
+        let as_black = GAMES.idx.black.prefix(player).range(deps.storage, min, max, order);
+        filtered_page(merge_by_id(as_white, as_black, order), &filter, limit)
+    }
+
+    /// Collect up to `limit` games matching `filter`, scanning at most `MAX_GAME_SCAN` games.
+    /// `next_start_after` is the last scanned id whenever the scan stopped before the end of
+    /// the range, so sparse filters page through the games instead of walking all of them.
+    fn filtered_page(
+        games: impl Iterator<Item = StdResult<ChessGame>>,
+        filter: &GameFilter,
+        limit: Option<u32>,
+    ) -> StdResult<GamesResponse> {
+        let limit = page_limit(limit);
+        let mut page = Vec::new();
+        let mut scanned = 0;
+        let mut last_scanned = None;
+        for game in games.take(MAX_GAME_SCAN) {
+            let game = game?;
+            scanned += 1;
+            last_scanned = Some(game.id);
+            if matches_filter(&game, filter) {
+                page.push(game);
+                if page.len() == limit {
+                    break;
                 }
             }
         }
 
-        Ok(GamesResponse { games: player_games })
+        let exhausted = page.len() < limit && scanned < MAX_GAME_SCAN;
+        Ok(GamesResponse {
+            games: page,
+            next_start_after: if exhausted { None } else { last_scanned },
+        })
     }
 
-    pub fn get_all_game_ids(deps: Deps) -> StdResult<GameIdsResponse> {
-        let game_ids: Vec<String> = GAME_IDS
-            .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+    /// Merge two iterators of games sorted by id in `order` into one, dropping duplicates
+    fn merge_by_id<'a>(
+        a: impl Iterator<Item = StdResult<(u64, ChessGame)>> + 'a,
+        b: impl Iterator<Item = StdResult<(u64, ChessGame)>> + 'a,
+        order: Order,
+    ) -> impl Iterator<Item = StdResult<ChessGame>> + 'a {
+        let mut a = a.peekable();
+        let mut b = b.peekable();
+        std::iter::from_fn(move || {
+            let take_a = match (a.peek(), b.peek()) {
+                (Some(Ok((id_a, _))), Some(Ok((id_b, _)))) => {
+                    if id_a == id_b {
+                        b.next();
+                        true
+                    } else {
+                        (id_a < id_b) == (order == Order::Ascending)
+                    }
+                }
+                (Some(Err(_)), _) | (Some(_), None) => true,
+                _ => false,
+            };
+            let next = if take_a { a.next() } else { b.next() };
+            next.map(|item| item.map(|(_, game)| game))
+        })
+    }
+
+    /// Key of the status index (`GameState::as_str`) matching `status`, None when it spans several keys
+    fn status_index_key(status: StatusFilter) -> Option<&'static str> {
+        match status {
+            StatusFilter::Active => Some("active"),
+            StatusFilter::WhiteWon => Some("white_won"),
+            StatusFilter::BlackWon => Some("black_won"),
+            StatusFilter::Draw => Some("draw"),
+            StatusFilter::Aborted => Some("aborted"),
+            StatusFilter::Finished => None,
+        }
+    }
+
+    pub fn list_games(
+        deps: Deps,
+        filter: GameFilter,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> StdResult<GamesResponse> {
+        let order = order.into();
+        let (min, max) = page_bounds(start_after, order);
+        let games: Box<dyn Iterator<Item = StdResult<(u64, ChessGame)>>> = match filter.status.and_then(status_index_key) {
+            Some(status) => Box::new(GAMES.idx.status.prefix(status.to_string()).range(deps.storage, min, max, order)),
+            None => Box::new(GAMES.range(deps.storage, min, max, order)),
+        };
+        filtered_page(games.map(|item| item.map(|(_, game)| game)), &filter, limit)
+    }
+
+    fn matches_filter(game: &ChessGame, filter: &GameFilter) -> bool {
+        let status_matches = match filter.status {
+            None => true,
+            Some(StatusFilter::Active) => game.status.is_active(),
+            Some(StatusFilter::Finished) => !game.status.is_active(),
+            Some(StatusFilter::WhiteWon) => matches!(game.status, GameState::WhiteWon { .. }),
+            Some(StatusFilter::BlackWon) => matches!(game.status, GameState::BlackWon { .. }),
+            Some(StatusFilter::Draw) => matches!(game.status, GameState::Draw { .. }),
+            Some(StatusFilter::Aborted) => game.status == GameState::Aborted,
+        };
+        status_matches
+            && (filter.termination.is_none() || game.status.termination() == filter.termination)
+            && filter.time_control.is_none_or(|kind| game.time_control.kind() == kind)
+    }
+
+    pub fn get_all_game_ids(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> StdResult<GameIdsResponse> {
+        let (min, max) = page_bounds(start_after, order.into());
+        let game_ids: Vec<u64> = GAMES
+            .keys(deps.storage, min, max, order.into())
+            .take(page_limit(limit))
             .collect::<StdResult<Vec<_>>>()?;
 
         Ok(GameIdsResponse { game_ids })
     }
 
-    pub fn check_time_status(deps: Deps, env: Env, game_id: String) -> StdResult<TimeStatusResponse> {
+    /// Time since the last move in the game's clock unit
+    pub fn time_since_last_move(game: &ChessGame, block: &BlockInfo) -> u64 {
+        match game.clock_unit {
+            ClockUnit::Blocks => block.height.saturating_sub(game.last_move_block),
+            ClockUnit::Seconds => block.time.seconds().saturating_sub(game.last_move_time),
+        }
+    }
+
+    /// Convert a duration in `unit` to seconds using the average block time
+    pub fn to_seconds(duration: u64, unit: ClockUnit, avg_block_time_ms: u64) -> u64 {
+        match unit {
+            ClockUnit::Blocks => duration.saturating_mul(avg_block_time_ms) / 1_000,
+            ClockUnit::Seconds => duration,
+        }
+    }
+
+    /// Rating category of a time control, from the time a player is expected to use in a
+    /// 40 move game
+    pub fn rating_category(time_control: &TimeControl, unit: ClockUnit, avg_block_time_ms: u64) -> RatingCategory {
+        let estimated = match *time_control {
+            TimeControl::Fischer { initial, increment: extra } | TimeControl::Bronstein { initial, delay: extra } => {
+                initial.saturating_add(extra.saturating_mul(40))
+            }
+            TimeControl::Correspondence { .. } | TimeControl::Unlimited => return RatingCategory::Correspondence,
+        };
+        RatingCategory::from_estimated_seconds(to_seconds(estimated, unit, avg_block_time_ms))
+    }
+
+    /// Convert a duration in `unit` to blocks using the average block time
+    pub fn to_blocks(duration: u64, unit: ClockUnit, avg_block_time_ms: u64) -> u64 {
+        match unit {
+            ClockUnit::Blocks => duration,
+            ClockUnit::Seconds => duration.saturating_mul(1_000) / avg_block_time_ms.max(1),
+        }
+    }
+
+    /// Remaining time of both players at `block`, with the elapsed time deducted
+    /// from the clock of the player to move
+    pub fn clocks_at(game: &ChessGame, block: &BlockInfo) -> (u64, u64) {
+        // Time is only tracked after both players' first moves and while the game is running
+        if game.move_count < 2 || !game.status.is_active() {
+            return (game.white_time_remaining, game.black_time_remaining);
+        }
+
+        let time_since_last_move = time_since_last_move(game, block);
+        match game.current_turn {
+            Color::White => (
+                game.white_time_remaining.saturating_sub(time_since_last_move),
+                game.black_time_remaining,
+            ),
+            Color::Black => (
+                game.white_time_remaining,
+                game.black_time_remaining.saturating_sub(time_since_last_move),
+            ),
+        }
+    }
+
+    /// Whether the player to move has run out of time at `block`
+    pub fn time_expired(game: &ChessGame, block: &BlockInfo) -> bool {
+        if game.move_count < 2 || !game.status.is_active() {
+            return false;
+        }
+        let remaining = match game.current_turn {
+            Color::White => game.white_time_remaining,
+            Color::Black => game.black_time_remaining,
+        };
+        game.time_control.has_expired(remaining, time_since_last_move(game, block))
+    }
+
+    pub fn check_time_status(deps: Deps, env: Env, game_id: u64) -> StdResult<TimeStatusResponse> {
         let game = GAMES.load(deps.storage, game_id)?;
+        let block_time_ms = CONFIG.load(deps.storage)?.avg_block_time_ms;
         
         // Calculate time elapsed since last move
-        let time_since_last_move = env.block.height.saturating_sub(game.last_move_block);
+        let time_since_last_move = time_since_last_move(&game, &env.block);
         
         // Calculate actual time remaining for each player
-        let (white_time_remaining, black_time_remaining) = if game.move_count >= 2 {
-            // Time is being tracked after both players' first moves
-            let current_player_is_white = game.current_turn == "white";
-            
-            if current_player_is_white {
-                // White's turn - deduct time used from their clock
-                let white_remaining = game.white_time_remaining.saturating_sub(time_since_last_move);
-                (white_remaining, game.black_time_remaining)
-            } else {
-                // Black's turn - deduct time used from their clock
-                let black_remaining = game.black_time_remaining.saturating_sub(time_since_last_move);
-                (game.white_time_remaining, black_remaining)
-            }
-        } else {
-            // Time not tracked yet - return full time
-            (game.white_time_remaining, game.black_time_remaining)
-        };
+        let (white_time_remaining, black_time_remaining) = clocks_at(&game, &env.block);
         
         // Check if current player has run out of time
-        let time_expired = if game.move_count >= 2 {
-            let current_time = if game.current_turn == "white" {
-                white_time_remaining
-            } else {
-                black_time_remaining
-            };
-            current_time == 0
-        } else {
-            false
-        };
+        let time_expired = time_expired(&game, &env.block);
         
         Ok(TimeStatusResponse {
             white_time_remaining,
             black_time_remaining,
-            current_player: game.current_turn.clone(),
+            current_player: game.current_turn,
             time_expired,
             move_count: game.move_count,
             time_since_last_move,
+            time_control: game.time_control,
+            clock_unit: game.clock_unit,
+            white_seconds_remaining: to_seconds(white_time_remaining, game.clock_unit, block_time_ms),
+            black_seconds_remaining: to_seconds(black_time_remaining, game.clock_unit, block_time_ms),
+            white_blocks_remaining: to_blocks(white_time_remaining, game.clock_unit, block_time_ms),
+            black_blocks_remaining: to_blocks(black_time_remaining, game.clock_unit, block_time_ms),
+            seconds_since_last_move: env.block.time.seconds().saturating_sub(game.last_move_time),
+            blocks_since_last_move: env.block.height.saturating_sub(game.last_move_block),
         })
     }
 
+    /// Zobrist hash identifying a position for repetition purposes: placement, side to move,
+    /// castling rights and a legal en passant square
+    pub fn position_hash(pos: &Chess) -> u64 {
+        pos.zobrist_hash::<Zobrist64>(shakmaty::EnPassantMode::Legal).0
+    }
+
+    /// Hashes of the positions since the last pawn move or capture, found by replaying the
+    /// game's moves. Earlier positions can't repeat anymore.
+    pub fn replay_position_hashes(storage: &dyn Storage, game: &ChessGame) -> StdResult<Vec<u64>> {
+        let mut pos = Chess::default();
+        let mut hashes = vec![position_hash(&pos)];
+
+        for uci in load_moves(storage, game.id)? {
+            let chess_move = parse_stored_move(&pos, &uci)?;
+            pos.play_unchecked(&chess_move);
+            if pos.halfmoves() == 0 {
+                hashes.clear();
+            }
+            hashes.push(position_hash(&pos));
+        }
+
+        Ok(hashes)
+    }
+
+    /// Resolve a stored move against the position it was played in
+    pub fn parse_stored_move(pos: &Chess, uci: &UciMove) -> StdResult<Move> {
+        resolve_uci_move(pos, uci)
+            .ok_or_else(|| cosmwasm_std::StdError::generic_err(format!("Illegal stored move: {}", uci)))
+    }
+
+    /// Moves of the game in Standard Algebraic Notation, found by replaying them
+    pub fn replay_san(storage: &dyn Storage, game: &ChessGame) -> StdResult<Vec<SanPlus>> {
+        let mut pos = Chess::default();
+        load_moves(storage, game.id)?.iter()
+            .map(|uci| {
+                let chess_move = parse_stored_move(&pos, uci)?;
+                Ok(SanPlus::from_move_and_play_unchecked(&mut pos, &chess_move))
+            })
+            .collect()
+    }
+
+    /// PGN result token
+    pub fn pgn_result(status: &GameState) -> &'static str {
+        match status {
+            GameState::WhiteWon { .. } => "1-0",
+            GameState::BlackWon { .. } => "0-1",
+            GameState::Draw { .. } => "1/2-1/2",
+            GameState::Active | GameState::Aborted => "*",
+        }
+    }
+
+    /// Display name of a player, their address when they have no profile
+    pub fn username(storage: &dyn Storage, player: &Addr) -> StdResult<String> {
+        Ok(USER_PROFILES.may_load(storage, player.clone())?
+            .map(|profile| profile.username)
+            .unwrap_or_else(|| player.to_string()))
+    }
+
+    /// PGN `Termination` tag value
+    fn pgn_termination(status: &GameState) -> &'static str {
+        match status.termination() {
+            None => "unterminated",
+            Some(Termination::Timeout) => "time forfeit",
+            Some(Termination::Adjudication) => "adjudication",
+            Some(Termination::Aborted) => "abandoned",
+            Some(_) => "normal",
+        }
+    }
+
+    /// PGN `TimeControl` tag value, in seconds
+    fn pgn_time_control(game: &ChessGame, avg_block_time_ms: u64) -> String {
+        let seconds = |duration| to_seconds(duration, game.clock_unit, avg_block_time_ms);
+        match game.time_control {
+            TimeControl::Fischer { initial, increment: extra } | TimeControl::Bronstein { initial, delay: extra } => {
+                format!("{}+{}", seconds(initial), seconds(extra))
+            }
+            TimeControl::Correspondence { per_move } => format!("1/{}", seconds(per_move)),
+            TimeControl::Unlimited => "-".to_string(),
+        }
+    }
+
+    /// `YYYY.MM.DD` date of a unix timestamp
+    fn pgn_date(timestamp: u64) -> String {
+        // Civil from days, see http://howardhinnant.github.io/date_algorithms.html
+        let days = timestamp / 86_400 + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+        let year = era * 400 + year_of_era + u64::from(month <= 2);
+        format!("{:04}.{:02}.{:02}", year, month, day)
+    }
+
+    /// The game as PGN: Seven Tag Roster, time control and termination tags and SAN movetext.
+    /// The date is estimated from the block the game started in.
+    pub fn game_pgn(storage: &dyn Storage, block: &BlockInfo, game: &ChessGame) -> StdResult<String> {
+        let config = CONFIG.load(storage)?;
+        let elapsed_ms = block.height.saturating_sub(game.created_block).saturating_mul(config.avg_block_time_ms);
+        let started = block.time.seconds().saturating_sub(elapsed_ms / 1_000);
+        let result = pgn_result(&game.status);
+
+        let tags = [
+            ("Event", format!("{} {} game", if game.rated { "Rated" } else { "Casual" }, game.category.as_str())),
+            ("Site", "Xion Chess".to_string()),
+            ("Date", pgn_date(started)),
+            ("Round", "-".to_string()),
+            ("White", username(storage, &game.white)?),
+            ("Black", username(storage, &game.black)?),
+            ("Result", result.to_string()),
+            ("TimeControl", pgn_time_control(game, config.avg_block_time_ms)),
+            ("Termination", pgn_termination(&game.status).to_string()),
+        ];
+        let mut pgn: String = tags.iter()
+            .map(|(name, value)| format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")))
+            .collect();
+        pgn.push('\n');
+
+        // Movetext lines are kept within 80 characters
+        let mut tokens = Vec::new();
+        for (ply, san) in replay_san(storage, game)?.iter().enumerate() {
+            if ply % 2 == 0 {
+                tokens.push(format!("{}.", ply / 2 + 1));
+            }
+            tokens.push(san.to_string());
+        }
+        tokens.push(result.to_string());
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > 80 {
+                pgn.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                pgn.push(' ');
+                line_length += 1;
+            }
+            line_length += token.len();
+            pgn.push_str(&token);
+        }
+        pgn.push('\n');
+        Ok(pgn)
+    }
+
+    pub fn get_game_pgn(deps: Deps, env: Env, game_id: u64) -> StdResult<PgnResponse> {
+        let game = GAMES.load(deps.storage, game_id)?;
+        Ok(PgnResponse { pgn: game_pgn(deps.storage, &env.block, &game)? })
+    }
+
+    /// Number of times the current position has occurred in the game
+    pub fn count_repetitions(deps: Deps, game: &ChessGame) -> StdResult<u32> {
+        let hash = position_hash(&parse_position(&game.current_fen)?);
+        Ok(POSITION_COUNTS.may_load(deps.storage, (game.id, hash))?.unwrap_or_default())
+    }
+
+    pub fn get_challenge(deps: Deps, challenge_id: u64) -> StdResult<ChallengeResponse> {
+        let challenge = CHALLENGES.load(deps.storage, challenge_id)?;
+        Ok(ChallengeResponse { challenge })
+    }
+
+    pub fn list_open_challenges(
+        deps: Deps,
+        player: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> StdResult<ChallengesResponse> {
+        let player = player.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+        let (min, max) = page_bounds(start_after, order.into());
+
+        let challenges = CHALLENGES
+            .range(deps.storage, min, max, order.into())
+            .map(|item| item.map(|(_, challenge)| challenge))
+            .filter(|item| match (item, &player) {
+                // Accepted random color challenges only wait for the reveal
+                (Ok(challenge), _) if challenge.pending_reveal.is_some() => false,
+                (Ok(challenge), Some(player)) => {
+                    challenge.challenger != player
+                        && challenge.opponent.as_ref().is_none_or(|opponent| opponent == player)
+                }
+                _ => true,
+            })
+            .take(page_limit(limit))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(ChallengesResponse { challenges })
+    }
+
+    pub fn get_config(deps: Deps) -> StdResult<ConfigResponse> {
+        let config = CONFIG.load(deps.storage)?;
+        Ok(ConfigResponse { config })
+    }
+
     pub fn get_user_profile(deps: Deps, address: Addr) -> StdResult<UserProfileResponse> {
-        let profile = USER_PROFILES.may_load(deps.storage, address)?;
-        Ok(UserProfileResponse { profile })
+        let profile = USER_PROFILES.may_load(deps.storage, address.clone())?;
+        let mut ratings = Vec::new();
+        for category in RatingCategory::ALL {
+            if let Some(rating) = category_ratings(category).may_load(deps.storage, address.clone())? {
+                ratings.push(CategoryRating { category, rating });
+            }
+        }
+        Ok(UserProfileResponse { profile, ratings })
+    }
+
+    pub fn get_rating_history(
+        deps: Deps,
+        player: Addr,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> StdResult<RatingHistoryResponse> {
+        let (min, max) = page_bounds(start_after, order.into());
+        let entries = RATING_HISTORY
+            .prefix(player)
+            .range(deps.storage, min, max, order.into())
+            .take(page_limit(limit))
+            .map(|item| item.map(|(_, change)| change))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(RatingHistoryResponse { entries })
     }
 
-    pub fn get_all_users(deps: Deps) -> StdResult<UsersResponse> {
+    pub fn get_move_history(
+        deps: Deps,
+        game_id: u64,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<MoveHistoryResponse> {
+        let (min, max) = page_bounds(start_after, Order::Ascending);
+        let played = load_moves(deps.storage, game_id)?;
+        let moves = MOVE_HISTORY
+            .prefix(game_id)
+            .range(deps.storage, min, max, Order::Ascending)
+            .take(page_limit(limit))
+            .map(|item| {
+                let (_, record) = item?;
+                let uci = played.get(record.ply as usize - 1)
+                    .ok_or_else(|| cosmwasm_std::StdError::not_found(format!("move {} of game {}", record.ply, game_id)))?;
+                Ok(MoveHistoryEntry {
+                    ply: record.ply,
+                    uci: uci.to_string(),
+                    san: record.san,
+                    fen: record.fen,
+                    clock_remaining: record.clock_remaining,
+                    block_height: record.block_height,
+                    block_time: record.block_time,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(MoveHistoryResponse { moves })
+    }
+
+    pub fn leaderboard(
+        deps: Deps,
+        start_after: Option<LeaderboardCursor>,
+        limit: Option<u32>,
+        include_provisional: bool,
+        active_since: Option<u64>,
+        category: Option<RatingCategory>,
+    ) -> StdResult<LeaderboardResponse> {
+        let (mut rank, max) = match start_after {
+            Some(cursor) => (cursor.rank, Some(Bound::exclusive((cursor.rating, cursor.address)))),
+            None => (0, None),
+        };
+        // Overall ratings live in the profile, so its username comes along with them
+        type RankedPlayer = (Addr, Rating, Option<String>);
+        let ratings: Box<dyn Iterator<Item = StdResult<RankedPlayer>>> = match category {
+            Some(category) => Box::new(category_ratings(category).idx.elo
+                .range(deps.storage, None, max, Order::Descending)
+                .map(|item| item.map(|(address, rating)| (address, rating, None)))),
+            None => Box::new(USER_PROFILES.idx.elo
+                .range(deps.storage, None, max, Order::Descending)
+                .map(|item| item.map(|(address, profile)| {
+                    let rating = profile.overall();
+                    (address, rating, Some(profile.username))
+                }))),
+        };
+
+        // Ranks are positions among the players passing the filters and carry over between pages
+        let limit = page_limit(limit);
+        let mut entries = Vec::new();
+        let mut scanned = 0;
+        let mut last_scanned = None;
+        for item in ratings.take(MAX_PLAYER_SCAN) {
+            let (address, rating, username) = item?;
+            scanned += 1;
+            last_scanned = Some((rating.elo, address.clone()));
+            if (!include_provisional && rating.is_provisional())
+                || active_since.is_some_and(|height| rating.last_game_at < height)
+            {
+                continue;
+            }
+            let username = match username {
+                Some(username) => username,
+                None => USER_PROFILES.may_load(deps.storage, address.clone())?
+                    .map(|profile| profile.username)
+                    .unwrap_or_default(),
+            };
+            rank += 1;
+            entries.push(LeaderboardEntry {
+                rank,
+                address,
+                username,
+                rating: rating.elo,
+                games_played: rating.games_played,
+            });
+            if entries.len() == limit {
+                break;
+            }
+        }
+
+        let exhausted = entries.len() < limit && scanned < MAX_PLAYER_SCAN;
+        let next_start_after = if exhausted {
+            None
+        } else {
+            last_scanned.map(|(rating, address)| LeaderboardCursor { rating, address, rank })
+        };
+        Ok(LeaderboardResponse { entries, next_start_after })
+    }
+
+    pub fn get_all_users(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> StdResult<UsersResponse> {
+        let start_after = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+        let (min, max) = page_bounds(start_after, order.into());
         let users: Vec<Addr> = USER_ADDRESSES
-            .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .keys(deps.storage, min, max, order.into())
+            .take(page_limit(limit))
             .collect::<StdResult<Vec<_>>>()?;
 
         Ok(UsersResponse { users })
@@ -1,4 +1,6 @@
 use cosmwasm_std::StdError;
+use cw_utils::PaymentError;
+use crate::state::ClockUnit;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,6 +8,9 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
     #[error("Invalid position claim: claimed {claimed}, actual {actual}")]
     InvalidClaim { claimed: String, actual: String },
 
@@ -15,9 +20,6 @@ pub enum ContractError {
     #[error("Invalid move: {details}")]
     InvalidMove { details: String },
 
-    #[error("Game already exists with ID: {id}")]
-    GameAlreadyExists { id: String },
-
     #[error("You are not a player in this game")]
     NotPlayerInGame {},
 
@@ -38,4 +40,97 @@ pub enum ContractError {
 
     #[error("Cannot respond to your own draw proposal")]
     CannotRespondToOwnProposal {},
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("An admin address is required to migrate a contract without config")]
+    AdminRequired {},
+
+    #[error("Cannot claim a timeout while your own clock is running")]
+    CannotClaimOwnTimeout {},
+
+    #[error("Clock has not started yet, both players must move first")]
+    ClockNotStarted {},
+
+    #[error("Opponent still has {remaining} {} on the clock", .unit.as_str())]
+    TimeNotExpired { remaining: u64, unit: ClockUnit },
+
+    #[error("Position has only occurred {occurrences} times, threefold repetition required")]
+    NoThreefoldRepetition { occurrences: u32 },
+
+    #[error("Only {halfmoves} halfmoves without capture or pawn move, 100 required")]
+    FiftyMoveRuleNotReached { halfmoves: u32 },
+
+    #[error("Games can only be aborted before both players have moved")]
+    AbortNotAllowed {},
+
+    #[error("Adjudication must end the game")]
+    InvalidAdjudication {},
+
+    #[error("Invalid time control: {reason}")]
+    InvalidTimeControl { reason: String },
+
+    #[error("Game has no clock")]
+    NoClock {},
+
+    #[error("Cannot challenge yourself")]
+    CannotChallengeSelf {},
+
+    #[error("Cannot accept your own challenge")]
+    CannotAcceptOwnChallenge {},
+
+    #[error("Challenge is addressed to another player")]
+    NotChallengeRecipient {},
+
+    #[error("Only the challenger can cancel a challenge")]
+    NotChallenger {},
+
+    #[error("Random colors need a 32 byte sha256 commitment, other colors none")]
+    InvalidColorCommitment {},
+
+    #[error("Accepting a random color challenge needs entropy")]
+    EntropyRequired {},
+
+    #[error("Challenge was accepted and waits for the color reveal")]
+    ChallengeAwaitsReveal {},
+
+    #[error("Challenge has not been accepted with a random color")]
+    NoPendingReveal {},
+
+    #[error("Secret does not match the color commitment")]
+    InvalidReveal {},
+
+    #[error("Reveal deadline has passed")]
+    RevealDeadlinePassed {},
+
+    #[error("Challenger can reveal until block {deadline}")]
+    RevealDeadlineNotReached { deadline: u64 },
+
+    #[error("Invalid wager: {reason}")]
+    InvalidWager { reason: String },
+
+    #[error("Funds must match the wager of {expected}")]
+    WagerNotMatched { expected: String },
+
+    #[error("House fee must be at most 10000 basis points and needs a treasury")]
+    InvalidHouseFee {},
+
+    #[error("CW20 token {address} is not accepted for wagers")]
+    Cw20NotAllowed { address: String },
+
+    #[error("CW20 tokens can only be sent to create or accept a challenge")]
+    InvalidReceiveMsg {},
+
+    #[error("Only finished games can be minted")]
+    GameNotFinished {},
+
+    #[error("Game has already been minted")]
+    GameAlreadyMinted {},
+
+    #[error("No NFT contract is configured")]
+    NftContractNotSet {},
+
+    #[error("Rating calculation is out of range")]
+    RatingOutOfRange {},
 }
@@ -1,10 +1,12 @@
 #[cfg(test)]
 mod tests {
     use crate::helpers::ChessVerifierContract;
-    use crate::msg::{InstantiateMsg, QueryMsg, GameStatus, VerificationResponse, MoveValidationResponse};
+    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, GameStatus, VerificationResponse, MoveValidationResponse};
+    use crate::state::{ColorChoice, TimeControl};
     use cosmwasm_std::testing::MockApi;
-    use cosmwasm_std::{Addr, Coin, Empty, Uint128, from_json};
-    use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
+    use cosmwasm_std::{Addr, Coin, Empty, Uint128};
+    use cw_multi_test::error::AnyResult;
+    use cw_multi_test::{App, AppBuilder, AppResponse, Contract, ContractWrapper, Executor};
 
     pub fn chess_verifier_contract() -> Box<dyn Contract<Empty>> {
         let contract = ContractWrapper::new(
@@ -39,7 +41,7 @@ mod tests {
         let mut app = mock_app();
         let contract_id = app.store_code(chess_verifier_contract());
 
-        let msg = InstantiateMsg {};
+        let msg = InstantiateMsg { admin: None, time_control_bounds: None, avg_block_time_ms: None, rating_system: None, house_fee_bps: None, treasury: None, cw20_allowlist: None, nft_contract: None };
         let contract_addr = app
             .instantiate_contract(
                 contract_id,
@@ -55,6 +57,24 @@ mod tests {
         (app, chess_contract)
     }
 
+    /// Start a rated game through a challenge `white` sends and `black` accepts
+    fn start_game(app: &mut App, contract: &ChessVerifierContract, white: &Addr, black: &Addr, time_control: TimeControl) -> AnyResult<AppResponse> {
+        let res = app.execute_contract(white.clone(), contract.addr(), &ExecuteMsg::CreateChallenge {
+            opponent: Some(black.to_string()),
+            color: ColorChoice::White,
+            time_control,
+            clock_unit: None,
+            rated: true,
+            color_commitment: None,
+        }, &[])?;
+        let challenge_id = res.events.iter()
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == "challenge_id")
+            .map(|attr| attr.value.parse().unwrap())
+            .unwrap();
+        app.execute_contract(black.clone(), contract.addr(), &ExecuteMsg::AcceptChallenge { challenge_id, entropy: None }, &[])
+    }
+
     mod chess_verification {
         use super::*;
 
@@ -139,4 +159,596 @@ mod tests {
             assert!(res.error.is_some());
         }
     }
+
+    mod game_clock {
+        use super::*;
+        use crate::msg::{ExecuteMsg, GameResponse};
+        use crate::state::{GameState, Termination, TimeControl};
+
+        fn play(app: &mut App, contract: &ChessVerifierContract, player: &Addr, uci: &str) -> AnyResult<AppResponse> {
+            app.execute_contract(player.clone(), contract.addr(), &ExecuteMsg::MakeMove {
+                game_id: 1,
+                from: uci[0..2].to_string(),
+                to: uci[2..4].to_string(),
+                promotion: None,
+            }, &[])
+        }
+
+        #[test]
+        fn timeout_loss_survives_the_transaction() {
+            let (mut app, contract) = proper_instantiate();
+            let white = app.api().addr_make("white");
+            let black = app.api().addr_make("black");
+
+            start_game(&mut app, &contract, &white, &black, TimeControl::Fischer { initial: 172_800, increment: 600 }).unwrap();
+            play(&mut app, &contract, &white, "e2e4").unwrap();
+            play(&mut app, &contract, &black, "e7e5").unwrap();
+
+            app.update_block(|block| block.height += 172_800);
+            play(&mut app, &contract, &white, "g1f3").unwrap();
+
+            let res: GameResponse = app
+                .wrap()
+                .query_wasm_smart(contract.addr(), &QueryMsg::GetGame { game_id: 1 })
+                .unwrap();
+            assert_eq!(res.game.unwrap().status, GameState::BlackWon { reason: Termination::Timeout });
+        }
+
+        #[test]
+        fn waiting_player_claims_timeout() {
+            let (mut app, contract) = proper_instantiate();
+            let white = app.api().addr_make("white");
+            let black = app.api().addr_make("black");
+
+            start_game(&mut app, &contract, &white, &black, TimeControl::Fischer { initial: 172_800, increment: 600 }).unwrap();
+            play(&mut app, &contract, &white, "e2e4").unwrap();
+            play(&mut app, &contract, &black, "e7e5").unwrap();
+
+            let claim = ExecuteMsg::ClaimTimeout { game_id: 1 };
+            app.update_block(|block| block.height += 100);
+            app.execute_contract(black.clone(), contract.addr(), &claim, &[]).unwrap_err();
+
+            app.update_block(|block| block.height += 172_800);
+            app.execute_contract(black.clone(), contract.addr(), &claim, &[]).unwrap();
+
+            let res: GameResponse = app
+                .wrap()
+                .query_wasm_smart(contract.addr(), &QueryMsg::GetGame { game_id: 1 })
+                .unwrap();
+            assert_eq!(res.game.unwrap().status, GameState::BlackWon { reason: Termination::Timeout });
+        }
+    }
+
+    mod challenges {
+        use super::*;
+        use crate::msg::{ChallengesResponse, ExecuteMsg, GameResponse, UserProfileResponse};
+        use crate::state::{ColorChoice, TimeControl};
+
+        fn challenge(app: &mut App, contract: &ChessVerifierContract, challenger: &Addr, opponent: Option<&Addr>) -> AnyResult<AppResponse> {
+            app.execute_contract(challenger.clone(), contract.addr(), &ExecuteMsg::CreateChallenge {
+                opponent: opponent.map(|addr| addr.to_string()),
+                color: ColorChoice::Black,
+                time_control: TimeControl::Fischer { initial: 600, increment: 5 },
+                clock_unit: None,
+                rated: true,
+                color_commitment: None,
+            }, &[])
+        }
+
+        fn open_challenges(app: &App, contract: &ChessVerifierContract, player: Option<&Addr>) -> ChallengesResponse {
+            app.wrap()
+                .query_wasm_smart(contract.addr(), &QueryMsg::ListOpenChallenges {
+                    player: player.map(|addr| addr.to_string()),
+                    start_after: None,
+                    limit: None,
+                    order: None,
+                })
+                .unwrap()
+        }
+
+        #[test]
+        fn open_challenge_starts_game_once_accepted() {
+            let (mut app, contract) = proper_instantiate();
+            let alice = app.api().addr_make("alice");
+            let bob = app.api().addr_make("bob");
+
+            challenge(&mut app, &contract, &alice, None).unwrap();
+            assert_eq!(open_challenges(&app, &contract, Some(&bob)).challenges.len(), 1);
+            assert!(open_challenges(&app, &contract, Some(&alice)).challenges.is_empty());
+
+            let err = app.execute_contract(alice.clone(), contract.addr(), &ExecuteMsg::AcceptChallenge { challenge_id: 1, entropy: None }, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Cannot accept your own challenge");
+
+            app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::AcceptChallenge { challenge_id: 1, entropy: None }, &[]).unwrap();
+            assert!(open_challenges(&app, &contract, None).challenges.is_empty());
+
+            let res: GameResponse = app
+                .wrap()
+                .query_wasm_smart(contract.addr(), &QueryMsg::GetGame { game_id: 1 })
+                .unwrap();
+            let game = res.game.unwrap();
+            assert_eq!(game.white, bob);
+            assert_eq!(game.black, alice);
+            assert_eq!(game.time_control, TimeControl::Fischer { initial: 600, increment: 5 });
+        }
+
+        #[test]
+        fn game_only_exists_once_the_opponent_accepts() {
+            let (mut app, contract) = proper_instantiate();
+            let alice = app.api().addr_make("alice");
+            let bob = app.api().addr_make("bob");
+            let game = |app: &App| -> GameResponse {
+                app.wrap().query_wasm_smart(contract.addr(), &QueryMsg::GetGame { game_id: 1 }).unwrap()
+            };
+
+            challenge(&mut app, &contract, &alice, Some(&bob)).unwrap();
+            assert!(game(&app).game.is_none());
+            let profile: UserProfileResponse = app.wrap()
+                .query_wasm_smart(contract.addr(), &QueryMsg::GetUserProfile { address: bob.clone() })
+                .unwrap();
+            assert!(profile.profile.is_none());
+
+            app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::AcceptChallenge { challenge_id: 1, entropy: None }, &[]).unwrap();
+            let started = game(&app).game.unwrap();
+            assert_eq!((started.white, started.black), (bob, alice));
+        }
+
+        #[test]
+        fn targeted_challenge_is_only_for_the_opponent() {
+            let (mut app, contract) = proper_instantiate();
+            let alice = app.api().addr_make("alice");
+            let bob = app.api().addr_make("bob");
+            let carol = app.api().addr_make("carol");
+
+            challenge(&mut app, &contract, &alice, Some(&bob)).unwrap();
+            assert!(open_challenges(&app, &contract, Some(&carol)).challenges.is_empty());
+
+            let err = app.execute_contract(carol.clone(), contract.addr(), &ExecuteMsg::AcceptChallenge { challenge_id: 1, entropy: None }, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Challenge is addressed to another player");
+            let err = app.execute_contract(carol.clone(), contract.addr(), &ExecuteMsg::DeclineChallenge { challenge_id: 1 }, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Challenge is addressed to another player");
+
+            app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::DeclineChallenge { challenge_id: 1 }, &[]).unwrap();
+            assert!(app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::AcceptChallenge { challenge_id: 1, entropy: None }, &[]).is_err());
+        }
+
+        #[test]
+        fn only_challenger_can_cancel() {
+            let (mut app, contract) = proper_instantiate();
+            let alice = app.api().addr_make("alice");
+            let bob = app.api().addr_make("bob");
+
+            challenge(&mut app, &contract, &alice, None).unwrap();
+            challenge(&mut app, &contract, &alice, None).unwrap();
+            let err = app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::CancelChallenge { challenge_id: 1 }, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Only the challenger can cancel a challenge");
+
+            app.execute_contract(alice.clone(), contract.addr(), &ExecuteMsg::CancelChallenge { challenge_id: 1 }, &[]).unwrap();
+            let remaining = open_challenges(&app, &contract, None).challenges;
+            assert_eq!(remaining.len(), 1);
+            assert_eq!(remaining[0].id, 2);
+        }
+    }
+
+    mod wagers {
+        use super::*;
+        use crate::msg::ExecuteMsg;
+        use crate::msg::{ConfigResponse, QueryMsg};
+        use crate::state::{ColorChoice, TimeControl, COLOR_REVEAL_BLOCKS};
+        use cosmwasm_std::{coins, Binary};
+        use sha2::{Digest, Sha256};
+
+        const STAKE_DENOM: &str = "uxion";
+
+        /// Alice and Bob start with 1000 each, a 2.5% house fee goes to the treasury
+        fn setup() -> (App, ChessVerifierContract, Addr, Addr, Addr) {
+            let api = MockApi::default();
+            let (alice, bob, treasury) = (api.addr_make("alice"), api.addr_make("bob"), api.addr_make("treasury"));
+            let mut app = AppBuilder::new().build(|router, _, storage| {
+                for player in [&alice, &bob] {
+                    router.bank.init_balance(storage, player, coins(1_000, STAKE_DENOM)).unwrap();
+                }
+            });
+            let contract_id = app.store_code(chess_verifier_contract());
+            let msg = InstantiateMsg {
+                admin: None,
+                time_control_bounds: None,
+                avg_block_time_ms: None,
+                rating_system: None,
+                house_fee_bps: Some(250),
+                treasury: Some(treasury.to_string()),
+                cw20_allowlist: None,
+                nft_contract: None,
+            };
+            let contract_addr = app
+                .instantiate_contract(contract_id, Addr::unchecked(ADMIN), &msg, &[], "chess-verifier", None)
+                .unwrap();
+            (app, ChessVerifierContract(contract_addr), alice, bob, treasury)
+        }
+
+        /// Alice offers a game to Bob, playing black
+        fn challenge(app: &mut App, contract: &ChessVerifierContract, alice: &Addr, bob: &Addr, stake: u128) -> AnyResult<AppResponse> {
+            app.execute_contract(alice.clone(), contract.addr(), &ExecuteMsg::CreateChallenge {
+                opponent: Some(bob.to_string()),
+                color: ColorChoice::Black,
+                time_control: TimeControl::Fischer { initial: 600, increment: 5 },
+                clock_unit: None,
+                rated: true,
+                color_commitment: None,
+            }, &coins(stake, STAKE_DENOM))
+        }
+
+        fn balance(app: &App, address: &Addr) -> u128 {
+            app.wrap().query_balance(address, STAKE_DENOM).unwrap().amount.u128()
+        }
+
+        #[test]
+        fn winner_takes_the_pot_minus_house_fee() {
+            let (mut app, contract, alice, bob, treasury) = setup();
+            challenge(&mut app, &contract, &alice, &bob, 100).unwrap();
+            app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::AcceptChallenge { challenge_id: 1, entropy: None }, &coins(100, STAKE_DENOM)).unwrap();
+            assert_eq!((balance(&app, &alice), balance(&app, &bob), balance(&app, &contract.addr())), (900, 900, 200));
+
+            app.execute_contract(alice.clone(), contract.addr(), &ExecuteMsg::ResignGame { game_id: 1 }, &[]).unwrap();
+            assert_eq!((balance(&app, &alice), balance(&app, &bob)), (900, 1_095));
+            assert_eq!((balance(&app, &treasury), balance(&app, &contract.addr())), (5, 0));
+        }
+
+        #[test]
+        fn draws_and_aborts_refund_both_players() {
+            let (mut app, contract, alice, bob, treasury) = setup();
+            for challenge_id in [1, 2] {
+                challenge(&mut app, &contract, &alice, &bob, 100).unwrap();
+                app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::AcceptChallenge { challenge_id, entropy: None }, &coins(100, STAKE_DENOM)).unwrap();
+            }
+            assert_eq!(balance(&app, &contract.addr()), 400);
+
+            app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::AbortGame { game_id: 1 }, &[]).unwrap();
+            assert_eq!((balance(&app, &alice), balance(&app, &bob)), (900, 900));
+
+            app.execute_contract(alice.clone(), contract.addr(), &ExecuteMsg::ProposeDrawRequest { game_id: 2 }, &[]).unwrap();
+            app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::RespondToDrawRequest { game_id: 2, accept: true }, &[]).unwrap();
+            assert_eq!((balance(&app, &alice), balance(&app, &bob)), (1_000, 1_000));
+            assert_eq!((balance(&app, &treasury), balance(&app, &contract.addr())), (0, 0));
+        }
+
+        #[test]
+        fn stake_has_to_be_matched() {
+            let (mut app, contract, alice, bob, _) = setup();
+            challenge(&mut app, &contract, &alice, &bob, 100).unwrap();
+
+            let accept = ExecuteMsg::AcceptChallenge { challenge_id: 1, entropy: None };
+            let err = app.execute_contract(bob.clone(), contract.addr(), &accept, &coins(50, STAKE_DENOM)).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Funds must match the wager of 100uxion");
+            let err = app.execute_contract(bob.clone(), contract.addr(), &accept, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Funds must match the wager of 100uxion");
+
+            // Declining returns the escrow
+            app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::DeclineChallenge { challenge_id: 1 }, &[]).unwrap();
+            assert_eq!((balance(&app, &alice), balance(&app, &contract.addr())), (1_000, 0));
+        }
+
+        #[test]
+        fn missed_color_reveal_forfeits_the_escrow() {
+            let (mut app, contract, alice, bob, _) = setup();
+            app.execute_contract(alice.clone(), contract.addr(), &ExecuteMsg::CreateChallenge {
+                opponent: Some(bob.to_string()),
+                color: ColorChoice::Random,
+                time_control: TimeControl::Fischer { initial: 600, increment: 5 },
+                clock_unit: None,
+                rated: true,
+                color_commitment: Some(Binary::from(Sha256::digest(b"secret").to_vec())),
+            }, &coins(100, STAKE_DENOM)).unwrap();
+            let accept = ExecuteMsg::AcceptChallenge { challenge_id: 1, entropy: Some(Binary::from(b"entropy")) };
+            app.execute_contract(bob.clone(), contract.addr(), &accept, &coins(100, STAKE_DENOM)).unwrap();
+
+            app.update_block(|block| block.height += COLOR_REVEAL_BLOCKS + 1);
+            let reveal = ExecuteMsg::RevealColor { challenge_id: 1, secret: Binary::from(b"secret") };
+            let err = app.execute_contract(alice.clone(), contract.addr(), &reveal, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Reveal deadline has passed");
+            app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::ClaimRevealTimeout { challenge_id: 1 }, &[]).unwrap();
+            assert_eq!((balance(&app, &alice), balance(&app, &bob), balance(&app, &contract.addr())), (900, 1_100, 0));
+        }
+
+        #[test]
+        fn funds_are_only_accepted_with_challenges() {
+            let (mut app, contract, alice, bob, _) = setup();
+            challenge(&mut app, &contract, &alice, &bob, 100).unwrap();
+            app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::AcceptChallenge { challenge_id: 1, entropy: None }, &coins(100, STAKE_DENOM)).unwrap();
+
+            let err = app.execute_contract(alice.clone(), contract.addr(), &ExecuteMsg::ResignGame { game_id: 1 }, &coins(10, STAKE_DENOM)).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "This message does no accept funds");
+            assert_eq!((balance(&app, &alice), balance(&app, &contract.addr())), (900, 200));
+        }
+
+        #[test]
+        fn cancelled_challenges_are_refunded() {
+            let (mut app, contract, alice, bob, _) = setup();
+            challenge(&mut app, &contract, &alice, &bob, 100).unwrap();
+            assert_eq!((balance(&app, &alice), balance(&app, &contract.addr())), (900, 100));
+
+            let cancel = ExecuteMsg::CancelChallenge { challenge_id: 1 };
+            let err = app.execute_contract(bob.clone(), contract.addr(), &cancel, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Only the challenger can cancel a challenge");
+            app.execute_contract(alice.clone(), contract.addr(), &cancel, &[]).unwrap();
+            assert_eq!((balance(&app, &alice), balance(&app, &bob), balance(&app, &contract.addr())), (1_000, 1_000, 0));
+        }
+
+        #[test]
+        fn house_fee_needs_a_treasury() {
+            let (mut app, contract, _, _, _) = setup();
+            let update = |house_fee_bps, treasury: Option<&str>, clear_treasury| ExecuteMsg::UpdateConfig {
+                admin: None,
+                time_control_bounds: None,
+                avg_block_time_ms: None,
+                rating_system: None,
+                house_fee_bps: Some(house_fee_bps),
+                treasury: treasury.map(str::to_string),
+                clear_treasury,
+                cw20_allowlist: None,
+                nft_contract: None,
+            };
+            let err = app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &update(10_001, None, false), &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "House fee must be at most 10000 basis points and needs a treasury");
+            // The treasury set at instantiation is kept
+            app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &update(500, None, false), &[]).unwrap();
+
+            // It can only be removed together with the fee
+            let err = app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &update(500, None, true), &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "House fee must be at most 10000 basis points and needs a treasury");
+            app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &update(0, None, true), &[]).unwrap();
+            let config: ConfigResponse = app.wrap().query_wasm_smart(contract.addr(), &QueryMsg::GetConfig {}).unwrap();
+            assert_eq!((config.config.house_fee_bps, config.config.treasury), (0, None));
+        }
+    }
+
+    mod cw20_wagers {
+        use super::*;
+        use crate::msg::{ExecuteMsg, ReceiveMsg};
+        use crate::state::{ColorChoice, TimeControl};
+        use cosmwasm_std::to_json_binary;
+        use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+
+        fn cw20_contract() -> Box<dyn Contract<Empty>> {
+            Box::new(ContractWrapper::new(
+                cw20_base::contract::execute,
+                cw20_base::contract::instantiate,
+                cw20_base::contract::query,
+            ))
+        }
+
+        fn instantiate_token(app: &mut App, code_id: u64, symbol: &str, holders: &[&Addr]) -> Addr {
+            let msg = cw20_base::msg::InstantiateMsg {
+                name: format!("{symbol} token"),
+                symbol: symbol.to_string(),
+                decimals: 6,
+                initial_balances: holders.iter()
+                    .map(|holder| Cw20Coin { address: holder.to_string(), amount: Uint128::new(1_000) })
+                    .collect(),
+                mint: None,
+                marketing: None,
+            };
+            app.instantiate_contract(code_id, Addr::unchecked(ADMIN), &msg, &[], symbol, None).unwrap()
+        }
+
+        /// Alice and Bob hold 1000 of an allowed and of an unlisted token, a 2.5% house fee goes to the treasury
+        fn setup() -> (App, ChessVerifierContract, Addr, Addr, Addr, Addr, Addr) {
+            let mut app = App::default();
+            let (alice, bob, treasury) = (app.api().addr_make("alice"), app.api().addr_make("bob"), app.api().addr_make("treasury"));
+            let cw20_id = app.store_code(cw20_contract());
+            let token = instantiate_token(&mut app, cw20_id, "CHESS", &[&alice, &bob]);
+            let unlisted = instantiate_token(&mut app, cw20_id, "OTHER", &[&alice, &bob]);
+
+            let contract_id = app.store_code(chess_verifier_contract());
+            let msg = InstantiateMsg {
+                admin: None,
+                time_control_bounds: None,
+                avg_block_time_ms: None,
+                rating_system: None,
+                house_fee_bps: Some(250),
+                treasury: Some(treasury.to_string()),
+                cw20_allowlist: Some(vec![token.to_string()]),
+                nft_contract: None,
+            };
+            let contract_addr = app
+                .instantiate_contract(contract_id, Addr::unchecked(ADMIN), &msg, &[], "chess-verifier", None)
+                .unwrap();
+            (app, ChessVerifierContract(contract_addr), alice, bob, treasury, token, unlisted)
+        }
+
+        fn send(app: &mut App, token: &Addr, from: &Addr, contract: &ChessVerifierContract, amount: u128, msg: &ReceiveMsg) -> AnyResult<AppResponse> {
+            app.execute_contract(from.clone(), token.clone(), &Cw20ExecuteMsg::Send {
+                contract: contract.addr().to_string(),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(msg).unwrap(),
+            }, &[])
+        }
+
+        /// Alice offers a game to Bob, playing black
+        fn create_challenge(bob: &Addr) -> ReceiveMsg {
+            ReceiveMsg::CreateChallenge {
+                opponent: Some(bob.to_string()),
+                color: ColorChoice::Black,
+                time_control: TimeControl::Fischer { initial: 600, increment: 5 },
+                clock_unit: None,
+                rated: true,
+                color_commitment: None,
+            }
+        }
+
+        fn balance(app: &App, token: &Addr, address: &Addr) -> u128 {
+            let response: BalanceResponse = app.wrap()
+                .query_wasm_smart(token, &Cw20QueryMsg::Balance { address: address.to_string() })
+                .unwrap();
+            response.balance.u128()
+        }
+
+        #[test]
+        fn winner_takes_the_pot_minus_house_fee() {
+            let (mut app, contract, alice, bob, treasury, token, _) = setup();
+            send(&mut app, &token, &alice, &contract, 100, &create_challenge(&bob)).unwrap();
+            send(&mut app, &token, &bob, &contract, 100, &ReceiveMsg::AcceptChallenge { challenge_id: 1, entropy: None }).unwrap();
+            assert_eq!(balance(&app, &token, &contract.addr()), 200);
+
+            app.execute_contract(alice.clone(), contract.addr(), &ExecuteMsg::ResignGame { game_id: 1 }, &[]).unwrap();
+            assert_eq!((balance(&app, &token, &alice), balance(&app, &token, &bob)), (900, 1_095));
+            assert_eq!((balance(&app, &token, &treasury), balance(&app, &token, &contract.addr())), (5, 0));
+        }
+
+        #[test]
+        fn cancelled_challenges_and_draws_are_refunded() {
+            let (mut app, contract, alice, bob, _, token, _) = setup();
+            send(&mut app, &token, &alice, &contract, 100, &create_challenge(&bob)).unwrap();
+            app.execute_contract(alice.clone(), contract.addr(), &ExecuteMsg::CancelChallenge { challenge_id: 1 }, &[]).unwrap();
+            assert_eq!((balance(&app, &token, &alice), balance(&app, &token, &contract.addr())), (1_000, 0));
+
+            send(&mut app, &token, &alice, &contract, 100, &create_challenge(&bob)).unwrap();
+            send(&mut app, &token, &bob, &contract, 100, &ReceiveMsg::AcceptChallenge { challenge_id: 2, entropy: None }).unwrap();
+            app.execute_contract(alice.clone(), contract.addr(), &ExecuteMsg::ProposeDrawRequest { game_id: 1 }, &[]).unwrap();
+            app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::RespondToDrawRequest { game_id: 1, accept: true }, &[]).unwrap();
+            assert_eq!((balance(&app, &token, &alice), balance(&app, &token, &bob)), (1_000, 1_000));
+        }
+
+        #[test]
+        fn tokens_sent_without_a_wager_message_are_returned() {
+            let (mut app, contract, alice, _, _, token, _) = setup();
+            let err = app.execute_contract(alice.clone(), token.clone(), &Cw20ExecuteMsg::Send {
+                contract: contract.addr().to_string(),
+                amount: Uint128::new(100),
+                msg: to_json_binary(&ExecuteMsg::ResignGame { game_id: 1 }).unwrap(),
+            }, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "CW20 tokens can only be sent to create or accept a challenge");
+            assert_eq!((balance(&app, &token, &alice), balance(&app, &token, &contract.addr())), (1_000, 0));
+        }
+
+        #[test]
+        fn stake_has_to_be_matched_in_the_same_token() {
+            let (mut app, contract, alice, bob, _, token, unlisted) = setup();
+            send(&mut app, &token, &alice, &contract, 100, &create_challenge(&bob)).unwrap();
+            let expected = format!("Funds must match the wager of 100 {token}");
+
+            let accept = ReceiveMsg::AcceptChallenge { challenge_id: 1, entropy: None };
+            let err = send(&mut app, &token, &bob, &contract, 50, &accept).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), expected);
+            let err = app.execute_contract(bob.clone(), contract.addr(), &ExecuteMsg::AcceptChallenge { challenge_id: 1, entropy: None }, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), expected);
+
+            let err = send(&mut app, &unlisted, &bob, &contract, 100, &accept).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), format!("CW20 token {unlisted} is not accepted for wagers"));
+            let err = send(&mut app, &unlisted, &alice, &contract, 100, &create_challenge(&bob)).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), format!("CW20 token {unlisted} is not accepted for wagers"));
+            assert_eq!(balance(&app, &unlisted, &contract.addr()), 0);
+        }
+    }
+
+    mod game_nfts {
+        use super::*;
+        use crate::msg::ExecuteMsg;
+        use crate::state::TimeControl;
+        use cw721::extension::Cw721OnchainExtensions;
+        use cw721::msg::{Cw721ExecuteMsg, Cw721InstantiateMsg, Cw721QueryMsg, NftInfoResponse, OwnerOfResponse};
+        use cw721::traits::{Cw721Execute, Cw721Query};
+        use cw721::{DefaultOptionalCollectionExtension, DefaultOptionalCollectionExtensionMsg, DefaultOptionalNftExtension, DefaultOptionalNftExtensionMsg};
+
+        type NftExecuteMsg = Cw721ExecuteMsg<DefaultOptionalNftExtensionMsg, DefaultOptionalCollectionExtensionMsg, Empty>;
+        type NftQueryMsg = Cw721QueryMsg<DefaultOptionalNftExtension, DefaultOptionalCollectionExtension, Empty>;
+
+        /// cw721 storing metadata on chain
+        fn cw721_contract() -> Box<dyn Contract<Empty>> {
+            Box::new(ContractWrapper::new(
+                |deps, env, info, msg: NftExecuteMsg| Cw721OnchainExtensions::default().execute(deps, &env, &info, msg),
+                |deps, env, info, msg: Cw721InstantiateMsg<DefaultOptionalCollectionExtensionMsg>| {
+                    Cw721OnchainExtensions::default().instantiate_with_version(deps, &env, &info, msg, "game-nfts", "0.1.0")
+                },
+                |deps, env, msg: NftQueryMsg| Cw721OnchainExtensions::default().query(deps, &env, msg),
+            ))
+        }
+
+        /// Chess contract minting on a cw721, with black having won game 1 by fool's mate
+        fn setup() -> (App, ChessVerifierContract, Addr, Addr, Addr) {
+            let (mut app, contract) = proper_instantiate();
+            let (white, black) = (app.api().addr_make("white"), app.api().addr_make("black"));
+            let cw721_id = app.store_code(cw721_contract());
+            let creator = app.api().addr_make("creator");
+            let nft_contract = app.instantiate_contract(cw721_id, creator, &Cw721InstantiateMsg::<DefaultOptionalCollectionExtensionMsg> {
+                name: "Xion Chess Games".to_string(),
+                symbol: "GAME".to_string(),
+                collection_info_extension: None,
+                minter: Some(contract.addr().to_string()),
+                creator: None,
+                withdraw_address: None,
+            }, &[], "game-nfts", None).unwrap();
+
+            start_game(&mut app, &contract, &white, &black, TimeControl::Unlimited).unwrap();
+            for (player, uci) in [(&white, "f2f3"), (&black, "e7e5"), (&white, "g2g4"), (&black, "d8h4")] {
+                app.execute_contract(player.clone(), contract.addr(), &ExecuteMsg::MakeMove {
+                    game_id: 1,
+                    from: uci[0..2].to_string(),
+                    to: uci[2..4].to_string(),
+                    promotion: None,
+                }, &[]).unwrap();
+            }
+            (app, contract, white, black, nft_contract)
+        }
+
+        fn set_nft_contract(app: &mut App, contract: &ChessVerifierContract, nft_contract: &Addr) {
+            app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &ExecuteMsg::UpdateConfig {
+                admin: None,
+                time_control_bounds: None,
+                avg_block_time_ms: None,
+                rating_system: None,
+                house_fee_bps: None,
+                treasury: None,
+                clear_treasury: false,
+                cw20_allowlist: None,
+                nft_contract: Some(nft_contract.to_string()),
+            }, &[]).unwrap();
+        }
+
+        #[test]
+        fn player_mints_finished_game_once() {
+            let (mut app, contract, _, black, nft_contract) = setup();
+            set_nft_contract(&mut app, &contract, &nft_contract);
+
+            app.execute_contract(black.clone(), contract.addr(), &ExecuteMsg::MintGameNft { game_id: 1 }, &[]).unwrap();
+            let owner: OwnerOfResponse = app.wrap()
+                .query_wasm_smart(&nft_contract, &NftQueryMsg::OwnerOf { token_id: "1".to_string(), include_expired: None })
+                .unwrap();
+            assert_eq!(owner.owner, black.to_string());
+
+            let info: NftInfoResponse<DefaultOptionalNftExtension> = app.wrap()
+                .query_wasm_smart(&nft_contract, &NftQueryMsg::NftInfo { token_id: "1".to_string() })
+                .unwrap();
+            let attributes = info.extension.unwrap().attributes.unwrap();
+            let attribute = |trait_type: &str| attributes.iter().find(|a| a.trait_type == trait_type).unwrap().value.clone();
+            assert_eq!(attribute("result"), "0-1");
+            assert_eq!(attribute("termination"), "checkmate");
+            assert_eq!(attribute("final_fen"), "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3");
+            assert!(attribute("pgn").ends_with("\n\n1. f3 e5 2. g4 Qh4# 0-1\n"));
+
+            let err = app.execute_contract(black.clone(), contract.addr(), &ExecuteMsg::MintGameNft { game_id: 1 }, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Game has already been minted");
+        }
+
+        #[test]
+        fn only_players_of_finished_games_can_mint() {
+            let (mut app, contract, white, _, nft_contract) = setup();
+            let mint = ExecuteMsg::MintGameNft { game_id: 1 };
+            let err = app.execute_contract(white.clone(), contract.addr(), &mint, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "No NFT contract is configured");
+            set_nft_contract(&mut app, &contract, &nft_contract);
+
+            let outsider = app.api().addr_make("outsider");
+            let err = app.execute_contract(outsider, contract.addr(), &mint, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "You are not a player in this game");
+
+            let black = app.api().addr_make("black");
+            start_game(&mut app, &contract, &white, &black, TimeControl::Unlimited).unwrap();
+            let err = app.execute_contract(white.clone(), contract.addr(), &ExecuteMsg::MintGameNft { game_id: 2 }, &[]).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Only finished games can be minted");
+
+            // The loser may keep the game too
+            app.execute_contract(white, contract.addr(), &mint, &[]).unwrap();
+        }
+    }
 }
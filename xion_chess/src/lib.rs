@@ -2,7 +2,9 @@ pub mod contract;
 mod error;
 pub mod helpers;
 pub mod integration_tests;
+pub mod move_encoding;
 pub mod msg;
+pub mod rating;
 pub mod state;
 
 #[cfg(test)]
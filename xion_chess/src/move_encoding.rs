@@ -0,0 +1,70 @@
+//! Compact move storage. Every ply is packed into 16 bits and a game's moves are kept apart
+//! from its `ChessGame` in fixed-size chunks, so recording a move writes at most one chunk no
+//! matter how long the game is.
+//!
+//! Layout of a packed move: destination square in bits 0-5, origin square in bits 6-11 and the
+//! promotion piece in bits 12-15 (0 for none, otherwise `Role` as u8, 2 = knight .. 5 = queen).
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+use cw_storage_plus::Map;
+use shakmaty::uci::UciMove;
+use shakmaty::{Role, Square};
+
+/// Plies per stored chunk
+pub const MOVE_CHUNK_PLIES: u32 = 16;
+
+// Packed moves keyed by (game_id, chunk). Chunks are written as raw big-endian bytes rather
+// than JSON, the map only provides the namespaced keys.
+const GAME_MOVES: Map<(u64, u32), Vec<u8>> = Map::new("game_moves");
+
+pub fn pack_move(uci: &UciMove) -> StdResult<u16> {
+    match uci {
+        UciMove::Normal { from, to, promotion } => {
+            let promotion = promotion.map_or(0, |role| role as u16);
+            Ok(promotion << 12 | (u32::from(*from) as u16) << 6 | u32::from(*to) as u16)
+        }
+        _ => Err(StdError::generic_err(format!("Can't pack move {}", uci))),
+    }
+}
+
+pub fn unpack_move(packed: u16) -> StdResult<UciMove> {
+    let promotion = match packed >> 12 {
+        0 => None,
+        2 => Some(Role::Knight),
+        3 => Some(Role::Bishop),
+        4 => Some(Role::Rook),
+        5 => Some(Role::Queen),
+        _ => return Err(StdError::generic_err(format!("Invalid packed move {:#06x}", packed))),
+    };
+    Ok(UciMove::Normal {
+        from: Square::new(u32::from(packed >> 6 & 0x3f)),
+        to: Square::new(u32::from(packed & 0x3f)),
+        promotion,
+    })
+}
+
+/// Record the move played at `ply`, counting from 0
+pub fn push_move(storage: &mut dyn Storage, game_id: u64, ply: u32, packed: u16) -> StdResult<()> {
+    let key = GAME_MOVES.key((game_id, ply / MOVE_CHUNK_PLIES));
+    let mut chunk = storage.get(&key).unwrap_or_default();
+    let offset = (ply % MOVE_CHUNK_PLIES) as usize * 2;
+    if chunk.len() != offset {
+        return Err(StdError::generic_err(format!("Move {} of game {} is out of order", ply, game_id)));
+    }
+    chunk.extend_from_slice(&packed.to_be_bytes());
+    storage.set(&key, &chunk);
+    Ok(())
+}
+
+/// All moves of a game in the order they were played
+pub fn load_moves(storage: &dyn Storage, game_id: u64) -> StdResult<Vec<UciMove>> {
+    let mut moves = Vec::new();
+    let mut chunk_index = 0;
+    while let Some(chunk) = storage.get(&GAME_MOVES.key((game_id, chunk_index))) {
+        for packed in chunk.chunks_exact(2) {
+            moves.push(unpack_move(u16::from_be_bytes([packed[0], packed[1]]))?);
+        }
+        chunk_index += 1;
+    }
+    Ok(moves)
+}
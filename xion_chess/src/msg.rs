@@ -1,9 +1,32 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Addr;
-use crate::state::{ChessGame, UserProfile};
+use cosmwasm_std::{Addr, Binary, Order};
+use cw20::Cw20ReceiveMsg;
+use crate::state::{Challenge, ChessGame, ClockUnit, Color, ColorChoice, Config, GameState, Rating, RatingCategory, RatingChange, RatingSystem, Termination, TimeControl, TimeControlBounds, TimeControlKind, UserProfile};
 
 #[cw_serde]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    /// Arbiter that can adjudicate disputed games, defaults to the instantiator
+    pub admin: Option<String>,
+    /// Limits on the time controls players may choose, defaults apply when omitted
+    pub time_control_bounds: Option<TimeControlBounds>,
+    /// Average block time used to convert between block and second clocks, defaults to 1000
+    pub avg_block_time_ms: Option<u64>,
+    /// Rating system for rated games, defaults to Elo
+    pub rating_system: Option<RatingSystem>,
+    /// Share of won wager pots sent to `treasury`, in basis points. Defaults to 0
+    pub house_fee_bps: Option<u16>,
+    pub treasury: Option<String>,
+    /// CW20 contracts whose tokens can be wagered, defaults to none
+    pub cw20_allowlist: Option<Vec<String>>,
+    /// cw721 contract finished games are minted on, this contract has to be its minter
+    pub nft_contract: Option<String>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Required when migrating a contract that has no config yet
+    pub admin: Option<String>,
+}
 
 #[cw_serde]
 pub enum ExecuteMsg {
@@ -16,37 +39,121 @@ pub enum ExecuteMsg {
         fen: String,
         claimed_status: GameStatus,
     },
-    /// Create a new chess game
-    CreateGame {
-        game_id: String,
-        opponent: Addr,
-        time_control: String,
+    /// Offer a game to a specific opponent, or to anyone when `opponent` is None.
+    /// Funds sent along are escrowed as a wager the opponent has to match.
+    CreateChallenge {
+        opponent: Option<String>,
+        /// Color the challenger plays
+        color: ColorChoice,
+        time_control: TimeControl,
+        clock_unit: Option<ClockUnit>,
+        rated: bool,
+        /// sha256 of a secret revealed with `RevealColor`, required for random colors only
+        color_commitment: Option<Binary>,
+    },
+    /// Accept a challenge, starting the game. Returns `GameCreatedResponse` data.
+    /// Wagered challenges need the same funds attached. Random color challenges need
+    /// `entropy` and only start once the challenger reveals their secret.
+    AcceptChallenge {
+        challenge_id: u64,
+        entropy: Option<Binary>,
+    },
+    /// Reveal the secret of an accepted random color challenge, settling the colors and
+    /// starting the game. Returns `GameCreatedResponse` data.
+    RevealColor {
+        challenge_id: u64,
+        secret: Binary,
     },
-    /// Make a move in a chess game
+    /// Take the whole escrow of an accepted random color challenge whose challenger let the
+    /// reveal deadline pass
+    ClaimRevealTimeout {
+        challenge_id: u64,
+    },
+    /// Decline a challenge addressed to the sender, refunding the challenger's wager
+    DeclineChallenge {
+        challenge_id: u64,
+    },
+    /// Withdraw one of the sender's challenges, refunding the wager
+    CancelChallenge {
+        challenge_id: u64,
+    },
+    /// Make a move in a chess game. `from` + `to` + `promotion` are interpreted as a UCI move
     MakeMove {
-        game_id: String,
+        game_id: u64,
         from: String,
         to: String,
         promotion: Option<String>,
     },
-    /// Update game status (resign, claim victory, etc.)
-    UpdateGameStatus {
-        game_id: String,
-        status: String,
+    /// Claim a win because the opponent's clock has run out
+    ClaimTimeout {
+        game_id: u64,
+    },
+    /// Claim a draw by threefold repetition or the fifty-move rule
+    ClaimDraw {
+        game_id: u64,
+        reason: DrawClaim,
+    },
+    /// Abort a game before both players have moved, no rating change
+    AbortGame {
+        game_id: u64,
+    },
+    /// Admin only: decide the result of a disputed game
+    AdjudicateGame {
+        game_id: u64,
+        result: GameState,
     },
+    /// Admin only: update contract configuration
+    UpdateConfig {
+        admin: Option<String>,
+        time_control_bounds: Option<TimeControlBounds>,
+        avg_block_time_ms: Option<u64>,
+        rating_system: Option<RatingSystem>,
+        house_fee_bps: Option<u16>,
+        treasury: Option<String>,
+        /// Remove the treasury, only allowed together with a house fee of 0
+        #[serde(default)]
+        clear_treasury: bool,
+        cw20_allowlist: Option<Vec<String>>,
+        nft_contract: Option<String>,
+    },
+    /// CW20 tokens sent with an embedded `ReceiveMsg`, escrowed as a challenge wager
+    Receive(Cw20ReceiveMsg),
     /// Resign from a game
     ResignGame {
-        game_id: String,
+        game_id: u64,
     },
     /// Propose a draw
     ProposeDrawRequest {
-        game_id: String,
+        game_id: u64,
     },
     /// Accept or decline a draw proposal
     RespondToDrawRequest {
-        game_id: String,
+        game_id: u64,
         accept: bool,
     },
+    /// Mint a finished game as an NFT owned by the sender, who must have played it
+    MintGameNft {
+        game_id: u64,
+    },
+}
+
+/// Messages embedded in `ExecuteMsg::Receive`, the received tokens are the wager
+#[cw_serde]
+pub enum ReceiveMsg {
+    CreateChallenge {
+        opponent: Option<String>,
+        /// Color the challenger plays
+        color: ColorChoice,
+        time_control: TimeControl,
+        clock_unit: Option<ClockUnit>,
+        rated: bool,
+        color_commitment: Option<Binary>,
+    },
+    /// Accept a challenge wagered with the same token and amount
+    AcceptChallenge {
+        challenge_id: u64,
+        entropy: Option<Binary>,
+    },
 }
 
 #[cw_serde]
@@ -56,7 +163,9 @@ pub enum QueryMsg {
     #[returns(VerificationResponse)]
     VerifyPosition { fen: String },
     
-    /// Check if a move is legal from current position
+    /// Check if a move is legal from current position.
+    /// `move_from` + `move_to` + `promotion` form a UCI move, so castling (`e1g1` or `e1h1`),
+    /// en passant and promotions are all accepted.
     #[returns(MoveValidationResponse)]
     ValidateMove { 
         current_fen: String,
@@ -67,27 +176,153 @@ pub enum QueryMsg {
 
     /// Get a specific game by ID
     #[returns(GameResponse)]
-    GetGame { game_id: String },
+    GetGame { game_id: u64 },
+
+    /// Export a game as PGN, replaying its moves into SAN
+    #[returns(PgnResponse)]
+    GetGamePgn { game_id: u64 },
 
-    /// Get all games for a player
+    /// Page through the plies of a game in order
+    #[returns(MoveHistoryResponse)]
+    GetMoveHistory {
+        game_id: u64,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+
+    /// Get a game by the client supplied id it had before ids were assigned by the contract
+    #[returns(GameResponse)]
+    GetGameByLegacyId { legacy_id: String },
+
+    /// Page through a player's games ordered by id
+    #[returns(GamesResponse)]
+    GetPlayerGames {
+        player: Addr,
+        filter: Option<GameFilter>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+
+    /// Page through all games ordered by id
     #[returns(GamesResponse)]
-    GetPlayerGames { player: Addr },
+    ListGames {
+        filter: Option<GameFilter>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
 
-    /// Get all game IDs
+    /// Page through game IDs
     #[returns(GameIdsResponse)]
-    GetAllGameIds {},
+    GetAllGameIds {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
 
     /// Check if a player's time has expired
     #[returns(TimeStatusResponse)]
-    CheckTimeStatus { game_id: String },
+    CheckTimeStatus { game_id: u64 },
 
-    /// Get user profile
+    /// Get user profile with the ratings of every category the user has played
     #[returns(UserProfileResponse)]
     GetUserProfile { address: Addr },
 
-    /// Get all users
+    /// Page through the rating changes of a player, ordered by when the games finished
+    #[returns(RatingHistoryResponse)]
+    GetRatingHistory {
+        player: Addr,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+
+    /// Players ranked by rating, highest first. `start_after` is the `next_start_after` of the
+    /// previous page.
+    #[returns(LeaderboardResponse)]
+    Leaderboard {
+        start_after: Option<LeaderboardCursor>,
+        limit: Option<u32>,
+        /// Include players whose rating is still provisional, defaults to false
+        include_provisional: Option<bool>,
+        /// Only players that finished a game at or after this block height
+        active_since: Option<u64>,
+        /// Rank by the ratings of one time control category instead of overall
+        category: Option<RatingCategory>,
+    },
+
+    /// Page through user addresses
     #[returns(UsersResponse)]
-    GetAllUsers {},
+    GetAllUsers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+
+    /// Get a pending challenge
+    #[returns(ChallengeResponse)]
+    GetChallenge { challenge_id: u64 },
+
+    /// List pending challenges ordered by id. With `player` set, only challenges that
+    /// player can accept: open ones and those addressed to them.
+    #[returns(ChallengesResponse)]
+    ListOpenChallenges {
+        player: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+
+    /// Get contract configuration
+    #[returns(ConfigResponse)]
+    GetConfig {},
+}
+
+/// Order of paginated results, `start_after` is interpreted in this direction
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Order {
+        match order {
+            SortOrder::Ascending => Order::Ascending,
+            SortOrder::Descending => Order::Descending,
+        }
+    }
+}
+
+/// Game state to filter listings by
+#[cw_serde]
+#[derive(Copy)]
+pub enum StatusFilter {
+    Active,
+    /// Any result, including aborted games
+    Finished,
+    WhiteWon,
+    BlackWon,
+    Draw,
+    Aborted,
+}
+
+/// Narrows down game listings, every field that is set must match
+#[cw_serde]
+#[derive(Default)]
+pub struct GameFilter {
+    pub status: Option<StatusFilter>,
+    pub termination: Option<Termination>,
+    pub time_control: Option<TimeControlKind>,
+}
+
+#[cw_serde]
+pub enum DrawClaim {
+    ThreefoldRepetition,
+    FiftyMoveRule,
 }
 
 #[cw_serde]
@@ -109,6 +344,10 @@ pub struct VerificationResponse {
 pub struct MoveValidationResponse {
     pub is_valid: bool,
     pub resulting_fen: Option<String>,
+    /// Normalized UCI of the resolved move (castling always as king-to-target, e.g. `e1g1`)
+    pub uci: Option<String>,
+    /// The move in Standard Algebraic Notation
+    pub san: Option<String>,
     pub error: Option<String>,
 }
 
@@ -117,32 +356,124 @@ pub struct GameResponse {
     pub game: Option<ChessGame>,
 }
 
+#[cw_serde]
+pub struct MoveHistoryResponse {
+    pub moves: Vec<MoveHistoryEntry>,
+}
+
+/// A `MoveRecord` together with the move played
+#[cw_serde]
+pub struct MoveHistoryEntry {
+    pub ply: u32,
+    pub uci: String,
+    pub san: String,
+    pub fen: String,
+    pub clock_remaining: Option<u64>,
+    pub block_height: Option<u64>,
+    pub block_time: Option<u64>,
+}
+
+#[cw_serde]
+pub struct PgnResponse {
+    pub pgn: String,
+}
+
 #[cw_serde]
 pub struct GamesResponse {
     pub games: Vec<ChessGame>,
+    /// Where to continue listing, None once every game has been looked at. Can be set on a
+    /// short or empty page when the filter skipped many games.
+    pub next_start_after: Option<u64>,
+}
+
+#[cw_serde]
+pub struct GameCreatedResponse {
+    pub game_id: u64,
 }
 
 #[cw_serde]
 pub struct GameIdsResponse {
-    pub game_ids: Vec<String>,
+    pub game_ids: Vec<u64>,
 }
 
 #[cw_serde]
 pub struct TimeStatusResponse {
+    /// Remaining time in the game's clock unit
     pub white_time_remaining: u64,
     pub black_time_remaining: u64,
-    pub current_player: String,
+    pub current_player: Color,
     pub time_expired: bool,
     pub move_count: u32,
     pub time_since_last_move: u64,
+    pub time_control: TimeControl,
+    pub clock_unit: ClockUnit,
+    /// Remaining time in both units, converted with the configured average block time
+    pub white_seconds_remaining: u64,
+    pub black_seconds_remaining: u64,
+    pub white_blocks_remaining: u64,
+    pub black_blocks_remaining: u64,
+    pub seconds_since_last_move: u64,
+    pub blocks_since_last_move: u64,
+}
+
+#[cw_serde]
+pub struct LeaderboardEntry {
+    pub rank: u32,
+    pub address: Addr,
+    pub username: String,
+    pub rating: u32,
+    pub games_played: u32,
+}
+
+#[cw_serde]
+pub struct RatingHistoryResponse {
+    pub entries: Vec<RatingChange>,
+}
+
+#[cw_serde]
+pub struct LeaderboardResponse {
+    pub entries: Vec<LeaderboardEntry>,
+    /// Where to continue listing, None once every player has been looked at
+    pub next_start_after: Option<LeaderboardCursor>,
+}
+
+/// Position in the leaderboard: the rating and address of the last player looked at and the
+/// rank given to the last player listed
+#[cw_serde]
+pub struct LeaderboardCursor {
+    pub rating: u32,
+    pub address: Addr,
+    pub rank: u32,
 }
 
 #[cw_serde]
 pub struct UserProfileResponse {
     pub profile: Option<UserProfile>,
+    pub ratings: Vec<CategoryRating>,
+}
+
+#[cw_serde]
+pub struct CategoryRating {
+    pub category: RatingCategory,
+    pub rating: Rating,
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub config: Config,
 }
 
 #[cw_serde]
 pub struct UsersResponse {
     pub users: Vec<Addr>,
 }
+
+#[cw_serde]
+pub struct ChallengeResponse {
+    pub challenge: Challenge,
+}
+
+#[cw_serde]
+pub struct ChallengesResponse {
+    pub challenges: Vec<Challenge>,
+}
@@ -0,0 +1,252 @@
+//! Deterministic rating math. Everything is integer fixed-point so results are identical on
+//! every node, floats are not allowed in CosmWasm. Arithmetic is checked and reports
+//! `ContractError::RatingOutOfRange` instead of panicking.
+
+use crate::error::ContractError;
+
+/// Fixed-point scale, 1.0 == SCALE
+pub const SCALE: i128 = 1_000_000_000;
+
+/// ln(2) in fixed-point
+const LN_2: i128 = 693_147_181;
+
+/// Scores are in thousandths: win 1000, draw 500, loss 0
+pub const SCORE_SCALE: u32 = 1000;
+
+fn mul(a: i128, b: i128) -> Result<i128, ContractError> {
+    a.checked_mul(b).map(|product| product / SCALE).ok_or(ContractError::RatingOutOfRange {})
+}
+
+fn div(a: i128, b: i128) -> Result<i128, ContractError> {
+    a.checked_mul(SCALE)
+        .and_then(|numerator| numerator.checked_div(b))
+        .ok_or(ContractError::RatingOutOfRange {})
+}
+
+/// e^x
+pub fn exp(x: i128) -> Result<i128, ContractError> {
+    // e^x = 2^k * e^r with |r| <= ln(2) / 2
+    let k = (x + x.signum() * LN_2 / 2) / LN_2;
+    if k < -60 {
+        return Ok(0);
+    }
+    let r = x - k * LN_2;
+
+    let mut term = SCALE;
+    let mut sum = SCALE;
+    for n in 1..=20 {
+        term = mul(term, r)? / n;
+        if term == 0 {
+            break;
+        }
+        sum += term;
+    }
+
+    Ok(if k >= 0 {
+        sum << k.min(60)
+    } else {
+        sum >> -k
+    })
+}
+
+/// Natural logarithm, `x` must be positive
+pub fn ln(x: i128) -> Result<i128, ContractError> {
+    if x <= 0 {
+        return Err(ContractError::RatingOutOfRange {});
+    }
+    // x = m * 2^k with m in [1, 2)
+    let mut m = x;
+    let mut k = 0;
+    while m >= 2 * SCALE {
+        m >>= 1;
+        k += 1;
+    }
+    while m < SCALE {
+        m <<= 1;
+        k -= 1;
+    }
+
+    // ln(m) = 2 * atanh(y) with y = (m - 1) / (m + 1) <= 1/3
+    let y = div(m - SCALE, m + SCALE)?;
+    let y2 = mul(y, y)?;
+    let mut power = y;
+    let mut sum = 0;
+    let mut n = 1;
+    while power != 0 {
+        sum += power / n;
+        power = mul(power, y2)?;
+        n += 2;
+    }
+
+    Ok(k * LN_2 + 2 * sum)
+}
+
+/// Square root, `x` must not be negative
+pub fn sqrt(x: i128) -> Result<i128, ContractError> {
+    u128::try_from(x).ok()
+        .and_then(|x| x.checked_mul(SCALE as u128))
+        .map(|x| x.isqrt() as i128)
+        .ok_or(ContractError::RatingOutOfRange {})
+}
+
+/// ln(10) / 400, turns an Elo rating difference into the exponent of the logistic curve
+const ELO_EXPONENT: i128 = 5_756_463;
+
+/// Expected score of a player `rating_diff` points above the opponent, in fixed-point:
+/// 1 / (1 + 10^(-rating_diff / 400))
+pub fn elo_expected(rating_diff: i64) -> Result<i128, ContractError> {
+    div(SCALE, SCALE + exp(-(rating_diff as i128) * ELO_EXPONENT)?)
+}
+
+/// FIDE style K-factor: 40 while provisional, 10 once the player has reached 2400, 20 otherwise
+pub fn elo_k_factor(provisional: bool, peak_rating: u32) -> u32 {
+    if provisional {
+        40
+    } else if peak_rating >= 2400 {
+        10
+    } else {
+        20
+    }
+}
+
+/// New Elo rating after scoring `score` against `opponent`, rounded to the nearest point
+/// and never below 100. Scores are in `SCORE_SCALE` units.
+pub fn elo_update(rating: u32, opponent: u32, score: u32, k_factor: u32) -> Result<u32, ContractError> {
+    let expected = elo_expected(rating as i64 - opponent as i64)?;
+    let change = k_factor as i128 * (score as i128 * SCALE / SCORE_SCALE as i128 - expected);
+    let change = (change + change.signum() * SCALE / 2) / SCALE;
+    Ok((rating as i128 + change).max(100) as u32)
+}
+
+/// Glicko-2 state of a player, in the usual rating scale
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glicko2 {
+    pub rating: u32,
+    /// Rating deviation
+    pub deviation: u32,
+    /// Volatility in millionths
+    pub volatility: u32,
+}
+
+pub const GLICKO2_DEFAULT_DEVIATION: u32 = 350;
+pub const GLICKO2_DEFAULT_VOLATILITY: u32 = 60_000;
+/// Bounds the inputs are clamped to, keeping deviations and volatilities away from zero
+pub const GLICKO2_MIN_DEVIATION: u32 = 30;
+pub const GLICKO2_MIN_VOLATILITY: u32 = 10_000;
+pub const GLICKO2_MAX_VOLATILITY: u32 = 200_000;
+
+impl Glicko2 {
+    fn clamped(self) -> Self {
+        Glicko2 {
+            rating: self.rating,
+            deviation: self.deviation.clamp(GLICKO2_MIN_DEVIATION, GLICKO2_DEFAULT_DEVIATION),
+            volatility: self.volatility.clamp(GLICKO2_MIN_VOLATILITY, GLICKO2_MAX_VOLATILITY),
+        }
+    }
+}
+
+/// Conversion between the rating scale and the Glicko-2 scale, 173.7178
+const GLICKO2_FACTOR: i128 = 173_717_800_000;
+/// System constant tau, limits how fast volatility changes
+const TAU: i128 = 500_000_000;
+/// Convergence tolerance of the volatility iteration
+const EPSILON: i128 = 1_000;
+const MAX_ITERATIONS: u32 = 100;
+/// pi^2
+const PI_SQUARED: i128 = 9_869_604_401;
+/// Expected scores are kept this far from 0 and 1 so the estimated variance stays finite
+/// between players hundreds of points apart
+const MIN_EXPECTED: i128 = 1_000_000;
+
+fn g(phi: i128) -> Result<i128, ContractError> {
+    div(SCALE, sqrt(SCALE + div(3 * mul(phi, phi)?, PI_SQUARED)?)?)
+}
+
+fn expected(mu: i128, mu_j: i128, g_j: i128) -> Result<i128, ContractError> {
+    let e = div(SCALE, SCALE + exp(-mul(g_j, mu - mu_j)?)?)?;
+    Ok(e.clamp(MIN_EXPECTED, SCALE - MIN_EXPECTED))
+}
+
+/// Rate `player` after one rating period with `results` against opponents, as described in
+/// Glickman's "Example of the Glicko-2 system". Scores are in `SCORE_SCALE` units, deviations
+/// and volatilities are clamped to the `GLICKO2_MIN_*` bounds first.
+pub fn glicko2_update(player: Glicko2, results: &[(Glicko2, u32)]) -> Result<Glicko2, ContractError> {
+    let player = player.clamped();
+    let to_mu = |rating: u32| div((rating as i128 - 1500) * SCALE, GLICKO2_FACTOR);
+    let to_phi = |deviation: u32| div(deviation as i128 * SCALE, GLICKO2_FACTOR);
+
+    let mu = to_mu(player.rating)?;
+    let phi = to_phi(player.deviation)?;
+    let sigma = player.volatility as i128 * (SCALE / 1_000_000);
+
+    if results.is_empty() {
+        let phi_star = sqrt(mul(phi, phi)? + mul(sigma, sigma)?)?;
+        let deviation = (mul(phi_star, GLICKO2_FACTOR)? + SCALE / 2) / SCALE;
+        return Ok(Glicko2 { deviation: deviation as u32, ..player });
+    }
+
+    // Estimated variance and improvement
+    let mut v_inverse = 0;
+    let mut improvement = 0;
+    for (opponent, score) in results {
+        let opponent = opponent.clamped();
+        let g_j = g(to_phi(opponent.deviation)?)?;
+        let e = expected(mu, to_mu(opponent.rating)?, g_j)?;
+        v_inverse += mul(mul(mul(g_j, g_j)?, e)?, SCALE - e)?;
+        improvement += mul(g_j, *score as i128 * SCALE / SCORE_SCALE as i128 - e)?;
+    }
+    let v = div(SCALE, v_inverse)?;
+    let delta = mul(v, improvement)?;
+
+    // New volatility with the Illinois algorithm
+    let phi2 = mul(phi, phi)?;
+    let delta2 = mul(delta, delta)?;
+    let a = ln(mul(sigma, sigma)?)?;
+    let f = |x: i128| -> Result<i128, ContractError> {
+        let ex = exp(x)?;
+        let denominator = phi2 + v + ex;
+        Ok(div(mul(ex, delta2 - phi2 - v - ex)?, 2 * mul(denominator, denominator)?)? - div(x - a, mul(TAU, TAU)?)?)
+    };
+
+    let mut big_a = a;
+    let mut big_b = if delta2 > phi2 + v {
+        ln(delta2 - phi2 - v)?
+    } else {
+        let mut k = 1;
+        while f(a - k * TAU)? < 0 && k < MAX_ITERATIONS as i128 {
+            k += 1;
+        }
+        a - k * TAU
+    };
+    let mut f_a = f(big_a)?;
+    let mut f_b = f(big_b)?;
+    for _ in 0..MAX_ITERATIONS {
+        if (big_b - big_a).abs() <= EPSILON || f_b == f_a {
+            break;
+        }
+        let big_c = big_a + mul(big_a - big_b, div(f_a, f_b - f_a)?)?;
+        let f_c = f(big_c)?;
+        if f_c.signum() * f_b.signum() <= 0 {
+            big_a = big_b;
+            f_a = f_b;
+        } else {
+            f_a /= 2;
+        }
+        big_b = big_c;
+        f_b = f_c;
+    }
+    let new_sigma = exp(big_a / 2)?;
+
+    // New deviation and rating
+    let phi_star2 = phi2 + mul(new_sigma, new_sigma)?;
+    let new_phi = div(SCALE, sqrt(div(SCALE, phi_star2)? + div(SCALE, v)?)?)?;
+    let new_mu = mu + mul(mul(new_phi, new_phi)?, improvement)?;
+
+    let rating = (mul(new_mu, GLICKO2_FACTOR)? + 1500 * SCALE + SCALE / 2).div_euclid(SCALE);
+    let deviation = (mul(new_phi, GLICKO2_FACTOR)? + SCALE / 2) / SCALE;
+    Ok(Glicko2 {
+        rating: rating.max(100) as u32,
+        deviation: deviation as u32,
+        volatility: ((new_sigma + 500) / 1_000) as u32,
+    })
+}
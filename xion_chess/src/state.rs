@@ -1,17 +1,345 @@
-use cosmwasm_std::Addr;
+use std::fmt;
+
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+use cw20::Cw20CoinVerified;
 use cosmwasm_schema::cw_serde;
-use cw_storage_plus::Map;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+
+use crate::rating::{elo_k_factor, GLICKO2_DEFAULT_DEVIATION, GLICKO2_DEFAULT_VOLATILITY};
+
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    pub fn other(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Color::White => "white",
+            Color::Black => "black",
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Color asked for by the player creating a game or challenge
+#[cw_serde]
+#[derive(Copy, Eq, Default)]
+pub enum ColorChoice {
+    #[default]
+    White,
+    Black,
+    /// Settled by hashing a secret the challenger commits to with entropy from the accepting
+    /// player, so neither of them picks the result
+    Random,
+}
+
+impl ColorChoice {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColorChoice::White => "white",
+            ColorChoice::Black => "black",
+            ColorChoice::Random => "random",
+        }
+    }
+}
+
+/// Why a game ended
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum Termination {
+    Checkmate,
+    Resignation,
+    Timeout,
+    Stalemate,
+    InsufficientMaterial,
+    Agreement,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+    FivefoldRepetition,
+    SeventyFiveMoveRule,
+    /// Result decided by the admin arbiter
+    Adjudication,
+    /// Game was called off without a result
+    Aborted,
+    /// Games finished before typed results existed
+    Unknown,
+}
+
+impl Termination {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Resignation => "resignation",
+            Termination::Timeout => "timeout",
+            Termination::Stalemate => "stalemate",
+            Termination::InsufficientMaterial => "insufficient_material",
+            Termination::Agreement => "agreement",
+            Termination::ThreefoldRepetition => "threefold_repetition",
+            Termination::FiftyMoveRule => "fifty_move_rule",
+            Termination::FivefoldRepetition => "fivefold_repetition",
+            Termination::SeventyFiveMoveRule => "seventy_five_move_rule",
+            Termination::Adjudication => "adjudication",
+            Termination::Aborted => "aborted",
+            Termination::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum GameState {
+    Active,
+    WhiteWon { reason: Termination },
+    BlackWon { reason: Termination },
+    Draw { reason: Termination },
+    Aborted,
+}
+
+impl GameState {
+    /// Outcome where `color` wins the game
+    pub fn won_by(color: Color, reason: Termination) -> GameState {
+        match color {
+            Color::White => GameState::WhiteWon { reason },
+            Color::Black => GameState::BlackWon { reason },
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self, GameState::Active)
+    }
+
+    pub fn winner(&self) -> Option<Color> {
+        match self {
+            GameState::WhiteWon { .. } => Some(Color::White),
+            GameState::BlackWon { .. } => Some(Color::Black),
+            _ => None,
+        }
+    }
+
+    /// How the game ended, `None` while it is still being played
+    pub fn termination(&self) -> Option<Termination> {
+        match self {
+            GameState::Active => None,
+            GameState::WhiteWon { reason }
+            | GameState::BlackWon { reason }
+            | GameState::Draw { reason } => Some(*reason),
+            GameState::Aborted => Some(Termination::Aborted),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameState::Active => "active",
+            GameState::WhiteWon { .. } => "white_won",
+            GameState::BlackWon { .. } => "black_won",
+            GameState::Draw { .. } => "draw",
+            GameState::Aborted => "aborted",
+        }
+    }
+}
+
+impl fmt::Display for GameState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a game's clock measures
+#[cw_serde]
+#[derive(Copy, Eq, Default)]
+pub enum ClockUnit {
+    /// Block height, drifts with chain speed
+    #[default]
+    Blocks,
+    /// Block time in seconds
+    Seconds,
+}
+
+impl ClockUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClockUnit::Blocks => "blocks",
+            ClockUnit::Seconds => "seconds",
+        }
+    }
+}
+
+/// Clock settings of a game. All durations are in the game's `ClockUnit`.
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum TimeControl {
+    /// Initial time plus an increment added after every move
+    Fischer { initial: u64, increment: u64 },
+    /// Initial time, time spent on a move is given back up to `delay`
+    Bronstein { initial: u64, delay: u64 },
+    /// Every move has to be made within `per_move`
+    Correspondence { per_move: u64 },
+    /// No clock, games can only end on the board, by agreement or resignation
+    Unlimited,
+}
+
+impl TimeControl {
+    pub fn kind(&self) -> TimeControlKind {
+        match self {
+            TimeControl::Fischer { .. } => TimeControlKind::Fischer,
+            TimeControl::Bronstein { .. } => TimeControlKind::Bronstein,
+            TimeControl::Correspondence { .. } => TimeControlKind::Correspondence,
+            TimeControl::Unlimited => TimeControlKind::Unlimited,
+        }
+    }
+
+    /// Clock both players start with
+    pub fn initial_clock(&self) -> u64 {
+        match self {
+            TimeControl::Fischer { initial, .. } | TimeControl::Bronstein { initial, .. } => *initial,
+            TimeControl::Correspondence { per_move } => *per_move,
+            TimeControl::Unlimited => 0,
+        }
+    }
+
+    /// Whether a player with `remaining` on the clock has run out of time after `elapsed`
+    pub fn has_expired(&self, remaining: u64, elapsed: u64) -> bool {
+        match self {
+            TimeControl::Unlimited => false,
+            _ => elapsed >= remaining,
+        }
+    }
+
+    /// Clock of the player after completing a move that took `elapsed`
+    pub fn clock_after_move(&self, remaining: u64, elapsed: u64) -> u64 {
+        match self {
+            TimeControl::Fischer { increment, .. } => {
+                remaining.saturating_sub(elapsed).saturating_add(*increment)
+            }
+            TimeControl::Bronstein { delay, .. } => {
+                remaining.saturating_sub(elapsed).saturating_add(elapsed.min(*delay))
+            }
+            TimeControl::Correspondence { per_move } => *per_move,
+            TimeControl::Unlimited => 0,
+        }
+    }
+}
+
+/// `TimeControl` without its durations, used to filter games
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum TimeControlKind {
+    Fischer,
+    Bronstein,
+    Correspondence,
+    Unlimited,
+}
+
+/// Limits on the time controls players may choose, set by the admin.
+/// Values are in seconds, block based clocks are converted with `Config::avg_block_time_ms`.
+#[cw_serde]
+pub struct TimeControlBounds {
+    pub min_initial: u64,
+    pub max_initial: u64,
+    pub max_increment: u64,  // Also the maximum Bronstein delay
+    pub min_per_move: u64,
+    pub max_per_move: u64,
+    pub allow_unlimited: bool,
+}
+
+impl Default for TimeControlBounds {
+    fn default() -> Self {
+        TimeControlBounds {
+            min_initial: 60,              // 1 minute
+            max_initial: 2_592_000,       // 30 days
+            max_increment: 86_400,        // 1 day
+            min_per_move: 3_600,          // 1 hour
+            max_per_move: 1_209_600,      // 14 days
+            allow_unlimited: true,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct Config {
+    pub admin: Addr,  // Arbiter for disputed games
+    #[serde(default)]
+    pub time_control_bounds: TimeControlBounds,
+    #[serde(default = "default_avg_block_time_ms")]
+    pub avg_block_time_ms: u64,  // Used to convert between block and second clocks
+    #[serde(default)]
+    pub rating_system: RatingSystem,
+    #[serde(default)]
+    pub house_fee_bps: u16,  // Share of a won wager pot sent to the treasury, in basis points
+    #[serde(default)]
+    pub treasury: Option<Addr>,
+    #[serde(default)]
+    pub cw20_allowlist: Vec<Addr>,  // CW20 contracts accepted as wagers
+    #[serde(default)]
+    pub nft_contract: Option<Addr>,  // cw721 contract finished games are minted on
+}
+
+/// How ratings are updated after rated games
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum RatingSystem {
+    #[default]
+    Elo,
+    /// Glicko-2 with every game as its own rating period
+    Glicko2,
+}
+
+pub fn default_avg_block_time_ms() -> u64 {
+    1_000
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
 
 #[cw_serde]
 pub struct UserProfile {
     pub username: String,
-    pub elo: u32,
-    pub games_played: u32,
+    pub elo: u32,  // Rating of the configured rating system
+    pub peak_elo: u32,  // Highest rating reached
+    pub deviation: u32,  // Glicko-2 rating deviation
+    pub volatility: u32,  // Glicko-2 volatility in millionths
+    pub games_played: u32,  // Rated and casual
+    pub casual_games: u32,
     pub wins: u32,
     pub draws: u32,
     pub losses: u32,
-    pub current_games: Vec<String>,  // Active game IDs
+    pub current_games: Vec<u64>,  // Active game IDs
     pub created_at: u64,  // Block height when created
+    pub last_game_at: u64,  // Block height when the last rated or casual game finished
+}
+
+/// Players with fewer finished rated games than this have a provisional rating
+pub const PROVISIONAL_GAMES: u32 = 30;
+
+impl UserProfile {
+    pub fn is_provisional(&self) -> bool {
+        self.overall().is_provisional()
+    }
+
+    /// Elo development coefficient for this player's next game
+    pub fn k_factor(&self) -> u32 {
+        self.overall().k_factor()
+    }
 }
 
 impl Default for UserProfile {
@@ -19,18 +347,241 @@ impl Default for UserProfile {
         UserProfile {
             username: String::new(),
             elo: 1200,  // Starting ELO
+            peak_elo: 1200,
+            deviation: GLICKO2_DEFAULT_DEVIATION,
+            volatility: GLICKO2_DEFAULT_VOLATILITY,
             games_played: 0,
+            casual_games: 0,
             wins: 0,
             draws: 0,
             losses: 0,
             current_games: Vec::new(),
             created_at: 0,
+            last_game_at: 0,
+        }
+    }
+}
+
+impl UserProfile {
+    /// Overall rating and record
+    pub fn overall(&self) -> Rating {
+        Rating {
+            elo: self.elo,
+            peak_elo: self.peak_elo,
+            deviation: self.deviation,
+            volatility: self.volatility,
+            games_played: self.games_played,
+            casual_games: self.casual_games,
+            wins: self.wins,
+            draws: self.draws,
+            losses: self.losses,
+            last_game_at: self.last_game_at,
+        }
+    }
+
+    pub fn set_overall(&mut self, rating: Rating) {
+        self.elo = rating.elo;
+        self.peak_elo = rating.peak_elo;
+        self.deviation = rating.deviation;
+        self.volatility = rating.volatility;
+        self.games_played = rating.games_played;
+        self.casual_games = rating.casual_games;
+        self.wins = rating.wins;
+        self.draws = rating.draws;
+        self.losses = rating.losses;
+        self.last_game_at = rating.last_game_at;
+    }
+}
+
+/// Time control category, each has its own ratings
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum RatingCategory {
+    Bullet,
+    Blitz,
+    Rapid,
+    Classical,
+    /// Games expected to take a day or more, including per move and unlimited clocks
+    Correspondence,
+}
+
+impl RatingCategory {
+    pub const ALL: [RatingCategory; 5] = [
+        RatingCategory::Bullet,
+        RatingCategory::Blitz,
+        RatingCategory::Rapid,
+        RatingCategory::Classical,
+        RatingCategory::Correspondence,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RatingCategory::Bullet => "bullet",
+            RatingCategory::Blitz => "blitz",
+            RatingCategory::Rapid => "rapid",
+            RatingCategory::Classical => "classical",
+            RatingCategory::Correspondence => "correspondence",
+        }
+    }
+
+    /// Category of a game expected to take `seconds` per player, initial time plus 40 increments
+    pub fn from_estimated_seconds(seconds: u64) -> RatingCategory {
+        match seconds {
+            0..180 => RatingCategory::Bullet,
+            180..480 => RatingCategory::Blitz,
+            480..1_500 => RatingCategory::Rapid,
+            1_500..86_400 => RatingCategory::Classical,
+            _ => RatingCategory::Correspondence,
+        }
+    }
+}
+
+/// Rating and record of a player in one category
+#[cw_serde]
+pub struct Rating {
+    pub elo: u32,
+    pub peak_elo: u32,
+    pub deviation: u32,
+    pub volatility: u32,
+    pub games_played: u32,  // Rated and casual
+    pub casual_games: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub last_game_at: u64,
+}
+
+impl Rating {
+    pub fn is_provisional(&self) -> bool {
+        self.games_played.saturating_sub(self.casual_games) < PROVISIONAL_GAMES
+    }
+
+    /// Elo development coefficient for this player's next game
+    pub fn k_factor(&self) -> u32 {
+        elo_k_factor(self.is_provisional(), self.peak_elo)
+    }
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        UserProfile::default().overall()
+    }
+}
+
+/// `UserProfile` as stored before game ids were assigned by the contract, only used by `migrate`
+#[cw_serde]
+pub struct LegacyUserProfile {
+    pub username: String,
+    pub elo: u32,
+    pub games_played: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub current_games: Vec<String>,
+    pub created_at: u64,
+}
+
+impl LegacyUserProfile {
+    /// Convert using `resolve` to map old game ids to new ones, dropping unknown games
+    pub fn into_profile(self, resolve: impl Fn(&str) -> Option<u64>) -> UserProfile {
+        UserProfile {
+            username: self.username,
+            elo: self.elo,
+            peak_elo: self.elo,
+            deviation: GLICKO2_DEFAULT_DEVIATION,
+            volatility: GLICKO2_DEFAULT_VOLATILITY,
+            games_played: self.games_played,
+            casual_games: 0,
+            wins: self.wins,
+            draws: self.draws,
+            losses: self.losses,
+            current_games: self.current_games.iter().filter_map(|id| resolve(id)).collect(),
+            created_at: self.created_at,
+            last_game_at: self.created_at,
         }
     }
 }
 
 #[cw_serde]
 pub struct ChessGame {
+    pub id: u64,
+    pub white: Addr,
+    pub black: Addr,
+    pub current_fen: String,
+    pub status: GameState,
+    pub current_turn: Color,
+    pub last_move_block: u64,
+    pub last_move_time: u64,  // Block time in seconds of the last move
+    pub white_time_remaining: u64,  // Clock units remaining for white
+    pub black_time_remaining: u64,  // Clock units remaining for black
+    pub created_block: u64,
+    pub claim_block: Option<u64>,
+    pub time_control: TimeControl,
+    pub clock_unit: ClockUnit,
+    pub category: RatingCategory,  // Ratings the result counts towards
+    pub rated: bool,  // Casual games leave ratings untouched
+    pub wager: Option<Wager>,  // Stake escrowed by each player
+    pub move_count: u32,  // Track total moves for increment calculation
+    pub draw_proposed_by: Option<String>,  // Address of player who proposed draw
+}
+
+/// Stake escrowed by each player of a wagered game
+#[cw_serde]
+pub enum Wager {
+    Native(Coin),
+    Cw20(Cw20CoinVerified),
+}
+
+impl Wager {
+    pub fn amount(&self) -> Uint128 {
+        match self {
+            Wager::Native(coin) => coin.amount,
+            Wager::Cw20(token) => token.amount,
+        }
+    }
+}
+
+impl std::fmt::Display for Wager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Wager::Native(coin) => write!(f, "{}", coin),
+            Wager::Cw20(token) => write!(f, "{} {}", token.amount, token.address),
+        }
+    }
+}
+
+/// A pending game offer, open to anyone or aimed at one opponent
+#[cw_serde]
+pub struct Challenge {
+    pub id: u64,
+    pub challenger: Addr,
+    pub opponent: Option<Addr>,  // None for open challenges
+    pub challenger_color: ColorChoice,
+    pub time_control: TimeControl,
+    pub clock_unit: ClockUnit,
+    pub rated: bool,
+    pub wager: Option<Wager>,  // Escrowed from the challenger, the opponent matches it
+    pub created_block: u64,
+    pub color_commitment: Option<Binary>,  // sha256 of the challenger's secret for random colors
+    pub pending_reveal: Option<PendingReveal>,  // Set once a random color challenge is accepted
+}
+
+/// Blocks a challenger has to reveal the secret of an accepted random color challenge,
+/// after that the opponent can claim the whole escrow
+pub const COLOR_REVEAL_BLOCKS: u64 = 600;
+
+/// Acceptance of a random color challenge, waiting for the challenger's secret
+#[cw_serde]
+pub struct PendingReveal {
+    pub opponent: Addr,
+    pub entropy: Binary,
+    pub deadline: u64,  // Last block the secret can be revealed in
+}
+
+/// `ChessGame` as stored before ids were assigned by the contract and status and turn
+/// were typed, only used by `migrate`
+#[cw_serde]
+pub struct LegacyChessGame {
     pub id: String,
     pub white: Addr,
     pub black: Addr,
@@ -39,23 +590,182 @@ pub struct ChessGame {
     pub status: String,
     pub current_turn: String,
     pub last_move_block: u64,
-    pub white_time_remaining: u64,  // Blocks remaining for white
-    pub black_time_remaining: u64,  // Blocks remaining for black
+    pub white_time_remaining: u64,
+    pub black_time_remaining: u64,
     pub created_block: u64,
     pub claim_block: Option<u64>,
     pub time_control: String,
-    pub move_count: u32,  // Track total moves for increment calculation
-    pub draw_proposed_by: Option<String>,  // Address of player who proposed draw
+    pub move_count: u32,
+    pub draw_proposed_by: Option<String>,
+}
+
+impl LegacyChessGame {
+    pub fn into_game(self, id: u64, last_move_time: u64) -> ChessGame {
+        let status = match self.status.as_str() {
+            "active" => GameState::Active,
+            "white_won" => GameState::WhiteWon { reason: Termination::Unknown },
+            "black_won" => GameState::BlackWon { reason: Termination::Unknown },
+            "draw" => GameState::Draw { reason: Termination::Unknown },
+            "stalemate" => GameState::Draw { reason: Termination::Stalemate },
+            // Anything else was set through the old free-form UpdateGameStatus
+            _ => GameState::Aborted,
+        };
+        let current_turn = if self.current_turn == "black" { Color::Black } else { Color::White };
+        // The old string was never read, clocks ran ~2 days with a 600 block increment up to
+        // move 20 and 60 after. Fischer increments are fixed, so games past move 20 keep 60 and
+        // younger games keep 600 until they end.
+        let increment = if self.move_count > 20 { 60 } else { 600 };
+        let time_control = TimeControl::Fischer { initial: 172_800, increment };
+
+        ChessGame {
+            id,
+            white: self.white,
+            black: self.black,
+            current_fen: self.current_fen,
+            status,
+            current_turn,
+            last_move_block: self.last_move_block,
+            last_move_time,
+            white_time_remaining: self.white_time_remaining,
+            black_time_remaining: self.black_time_remaining,
+            created_block: self.created_block,
+            claim_block: self.claim_block,
+            time_control,
+            clock_unit: ClockUnit::Blocks,
+            category: RatingCategory::Correspondence,
+            rated: true,
+            wager: None,
+            move_count: self.move_count,
+            draw_proposed_by: self.draw_proposed_by,
+        }
+    }
+}
+
+/// Secondary indexes on `GAMES`
+pub struct GameIndexes<'a> {
+    pub white: MultiIndex<'a, Addr, ChessGame, u64>,
+    pub black: MultiIndex<'a, Addr, ChessGame, u64>,
+    // Keyed by `GameState::as_str`
+    pub status: MultiIndex<'a, String, ChessGame, u64>,
 }
 
-// Game storage: game_id -> ChessGame
-pub const GAMES: Map<String, ChessGame> = Map::new("games");
+impl IndexList<ChessGame> for GameIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ChessGame>> + '_> {
+        let indexes: Vec<&dyn Index<ChessGame>> = vec![&self.white, &self.black, &self.status];
+        Box::new(indexes.into_iter())
+    }
+}
+
+// Game storage: game_id -> ChessGame, indexed by player and status
+pub const GAMES: IndexedMap<u64, ChessGame, GameIndexes> = IndexedMap::new("games_by_id", GameIndexes {
+    white: MultiIndex::new(|_pk, game| game.white.clone(), "games_by_id", "games_by_id__white"),
+    black: MultiIndex::new(|_pk, game| game.black.clone(), "games_by_id", "games_by_id__black"),
+    status: MultiIndex::new(|_pk, game| game.status.as_str().to_string(), "games_by_id", "games_by_id__status"),
+});
+
+// Id of the last created game
+pub const GAME_COUNT: Item<u64> = Item::new("game_count");
+
+// Games keyed by client supplied ids, emptied by `migrate`
+pub const LEGACY_GAMES: Map<String, LegacyChessGame> = Map::new("games");
+
+// Index of the client supplied ids, emptied by `migrate`
+pub const LEGACY_GAME_INDEX: Map<String, bool> = Map::new("game_ids");
+
+// Client supplied id -> id assigned during migration
+pub const LEGACY_GAME_IDS: Map<String, u64> = Map::new("legacy_game_ids");
+
+// Repetition tracking: (game_id, zobrist hash) -> number of times the position occurred
+pub const POSITION_COUNTS: Map<(u64, u64), u32> = Map::new("position_counts");
+
+/// One ply of a game. The move itself is only kept in the packed move list, see `move_encoding`.
+#[cw_serde]
+pub struct MoveRecord {
+    pub ply: u32,  // Starting at 1
+    pub san: String,
+    pub fen: String,  // Position after the move
+    pub clock_remaining: Option<u64>,  // Mover's clock after the move in clock units, None without a clock
+    pub block_height: Option<u64>,  // None for moves backfilled by migration
+    pub block_time: Option<u64>,  // Seconds
+}
+
+// Move history keyed by game id and `MoveRecord::ply`
+pub const MOVE_HISTORY: Map<(u64, u32), MoveRecord> = Map::new("move_history");
+
+// Pending challenges, removed once accepted, declined or cancelled
+pub const CHALLENGES: Map<u64, Challenge> = Map::new("challenges");
+
+// Id of the last created challenge
+pub const CHALLENGE_COUNT: Item<u64> = Item::new("challenge_count");
+
+// Games minted as NFTs -> player who minted them
+pub const GAME_NFTS: Map<u64, Addr> = Map::new("game_nfts");
+
+/// Secondary indexes on `USER_PROFILES`
+pub struct ProfileIndexes<'a> {
+    // Ordered by rating, ties by address
+    pub elo: MultiIndex<'a, u32, UserProfile, Addr>,
+}
+
+impl IndexList<UserProfile> for ProfileIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<UserProfile>> + '_> {
+        let indexes: Vec<&dyn Index<UserProfile>> = vec![&self.elo];
+        Box::new(indexes.into_iter())
+    }
+}
+
+// User profiles: address -> UserProfile, indexed by rating for the leaderboard
+pub const USER_PROFILES: IndexedMap<Addr, UserProfile, ProfileIndexes> = IndexedMap::new("user_profiles", ProfileIndexes {
+    elo: MultiIndex::new(|_pk, profile| profile.elo, "user_profiles", "user_profiles__elo"),
+});
+
+/// Secondary indexes on the category ratings
+pub struct RatingIndexes<'a> {
+    // Ordered by rating, ties by address
+    pub elo: MultiIndex<'a, u32, Rating, Addr>,
+}
+
+impl IndexList<Rating> for RatingIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Rating>> + '_> {
+        let indexes: Vec<&dyn Index<Rating>> = vec![&self.elo];
+        Box::new(indexes.into_iter())
+    }
+}
+
+/// Ratings of one category keyed by player
+pub fn category_ratings(category: RatingCategory) -> IndexedMap<Addr, Rating, RatingIndexes<'static>> {
+    let (namespace, elo_namespace) = match category {
+        RatingCategory::Bullet => ("ratings_bullet", "ratings_bullet__elo"),
+        RatingCategory::Blitz => ("ratings_blitz", "ratings_blitz__elo"),
+        RatingCategory::Rapid => ("ratings_rapid", "ratings_rapid__elo"),
+        RatingCategory::Classical => ("ratings_classical", "ratings_classical__elo"),
+        RatingCategory::Correspondence => ("ratings_correspondence", "ratings_correspondence__elo"),
+    };
+    IndexedMap::new(namespace, RatingIndexes {
+        elo: MultiIndex::new(|_pk, rating| rating.elo, namespace, elo_namespace),
+    })
+}
+
+/// A rating change caused by a rated game
+#[cw_serde]
+pub struct RatingChange {
+    pub index: u32,  // Number of the player's rated game, starting at 1
+    pub game_id: u64,
+    pub opponent: Addr,
+    pub category: RatingCategory,
+    pub old_rating: u32,
+    pub new_rating: u32,
+    pub old_category_rating: u32,
+    pub new_category_rating: u32,
+    pub block_height: u64,
+    pub block_time: u64,  // Seconds
+}
 
-// Index of all game IDs for listing
-pub const GAME_IDS: Map<String, bool> = Map::new("game_ids");
+// Rating history keyed by player and `RatingChange::index`
+pub const RATING_HISTORY: Map<(Addr, u32), RatingChange> = Map::new("rating_history");
 
-// User profiles: address -> UserProfile
-pub const USER_PROFILES: Map<Addr, UserProfile> = Map::new("user_profiles");
+// Same namespace as USER_PROFILES, read with the pre-migration layout
+pub const LEGACY_USER_PROFILES: Map<Addr, LegacyUserProfile> = Map::new("user_profiles");
 
 // Index of all user addresses
 pub const USER_ADDRESSES: Map<Addr, bool> = Map::new("user_addresses");
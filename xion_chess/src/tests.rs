@@ -1,16 +1,24 @@
 #[cfg(test)]
 mod unit_tests {
-    use super::*;
-    use crate::contract::{instantiate, query};
-    use crate::msg::{InstantiateMsg, QueryMsg, GameStatus, VerificationResponse, MoveValidationResponse};
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{from_json};
+    use crate::ContractError;
+    use crate::contract::{execute, instantiate, migrate, query};
+    use crate::contract::execute::random_color;
+    use crate::contract::query::MAX_GAME_SCAN;
+    use crate::msg::{DrawClaim, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, GameStatus, VerificationResponse, MoveValidationResponse, GameResponse, GamesResponse, GameCreatedResponse, GameIdsResponse, TimeStatusResponse, UserProfileResponse, UsersResponse, LeaderboardCursor, LeaderboardResponse, MoveHistoryEntry, MoveHistoryResponse, PgnResponse, RatingHistoryResponse, GameFilter, SortOrder, StatusFilter};
+    use crate::move_encoding::{load_moves, pack_move, push_move, unpack_move};
+    use crate::rating::{elo_expected, elo_update, glicko2_update, ln, Glicko2};
+    use crate::state::{ChessGame, ClockUnit, Color, ColorChoice, GameState, LegacyChessGame, RatingCategory, RatingSystem, Termination, TimeControl, TimeControlBounds, TimeControlKind, category_ratings, COLOR_REVEAL_BLOCKS, GAMES, LEGACY_GAMES, MOVE_HISTORY, POSITION_COUNTS, LEGACY_GAME_INDEX, LEGACY_USER_PROFILES, LegacyUserProfile, USER_ADDRESSES, USER_PROFILES, UserProfile};
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{from_json, to_json_string, Addr, Binary, Env, OwnedDeps, Response, Storage, Timestamp};
+    use sha2::{Digest, Sha256};
+    use shakmaty::uci::UciMove;
+    use shakmaty::{Chess, Position};
 
     #[test]
     fn proper_initialization() {
         let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {};
-        let info = mock_info("creator", &[]);
+        let msg = InstantiateMsg { admin: None, time_control_bounds: None, avg_block_time_ms: None, rating_system: None, house_fee_bps: None, treasury: None, cw20_allowlist: None, nft_contract: None };
+        let info = message_info(&deps.api.addr_make("creator"), &[]);
 
         let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(0, res.messages.len());
@@ -51,7 +59,7 @@ mod unit_tests {
     fn verify_stalemate_position() {
         let deps = mock_dependencies();
         // Stalemate position - king has no moves but is not in check
-        let stalemate_fen = "k7/8/1K6/8/8/8/8/1Q6 b - - 0 1";
+        let stalemate_fen = "k7/8/1Q6/8/8/8/8/7K b - - 0 1";
         
         let res = query(deps.as_ref(), mock_env(), QueryMsg::VerifyPosition { 
             fen: stalemate_fen.to_string() 
@@ -81,7 +89,8 @@ mod unit_tests {
         assert!(response.error.is_none());
         
         // Verify the resulting FEN is correct
-        let expected_fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+        // The en passant square is only written when an en passant capture is actually legal
+        let expected_fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
         assert_eq!(response.resulting_fen.unwrap(), expected_fen);
     }
 
@@ -144,7 +153,7 @@ mod unit_tests {
     fn validate_pawn_promotion() {
         let deps = mock_dependencies();
         // Position where white pawn can promote
-        let promotion_fen = "rnbqkbnr/pppppPpp/8/8/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1";
+        let promotion_fen = "7k/5P2/8/8/8/8/8/4K3 w - - 0 1";
         
         let res = query(deps.as_ref(), mock_env(), QueryMsg::ValidateMove { 
             current_fen: promotion_fen.to_string(),
@@ -162,7 +171,7 @@ mod unit_tests {
     #[test]
     fn validate_invalid_promotion_piece() {
         let deps = mock_dependencies();
-        let promotion_fen = "rnbqkbnr/pppppPpp/8/8/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1";
+        let promotion_fen = "7k/5P2/8/8/8/8/8/4K3 w - - 0 1";
         
         let res = query(deps.as_ref(), mock_env(), QueryMsg::ValidateMove { 
             current_fen: promotion_fen.to_string(),
@@ -177,4 +186,1510 @@ mod unit_tests {
         assert!(response.error.is_some());
         assert!(response.error.unwrap().contains("Invalid promotion"));
     }
-}
\ No newline at end of file
+
+    fn validate(fen: &str, from: &str, to: &str, promotion: Option<&str>) -> MoveValidationResponse {
+        let deps = mock_dependencies();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ValidateMove {
+            current_fen: fen.to_string(),
+            move_from: from.to_string(),
+            move_to: to.to_string(),
+            promotion: promotion.map(|p| p.to_string()),
+        }).unwrap();
+        from_json(&res).unwrap()
+    }
+
+    const CASTLING_FEN: &str = "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1";
+
+    #[test]
+    fn validate_kingside_castling() {
+        let response = validate(CASTLING_FEN, "e1", "g1", None);
+        assert!(response.is_valid);
+        assert_eq!(response.uci.unwrap(), "e1g1");
+        assert_eq!(response.resulting_fen.unwrap(), "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R4RK1 b kq - 1 1");
+    }
+
+    #[test]
+    fn validate_queenside_castling() {
+        let response = validate(CASTLING_FEN, "e1", "c1", None);
+        assert!(response.is_valid);
+        assert_eq!(response.uci.unwrap(), "e1c1");
+        assert_eq!(response.resulting_fen.unwrap(), "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/2KR3R b kq - 1 1");
+    }
+
+    #[test]
+    fn validate_castling_king_to_rook() {
+        // Chess960 style notation: king moves onto its own rook
+        let response = validate(CASTLING_FEN, "e1", "h1", None);
+        assert!(response.is_valid);
+        assert_eq!(response.uci.unwrap(), "e1g1");
+
+        let response = validate(CASTLING_FEN, "e1", "a1", None);
+        assert!(response.is_valid);
+        assert_eq!(response.uci.unwrap(), "e1c1");
+    }
+
+    #[test]
+    fn validate_black_castling() {
+        let fen = "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R b KQkq - 0 1";
+        let response = validate(fen, "e8", "g8", None);
+        assert!(response.is_valid);
+        assert_eq!(response.resulting_fen.unwrap(), "r4rk1/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQ - 1 2");
+
+        let response = validate(fen, "e8", "a8", None);
+        assert!(response.is_valid);
+        assert_eq!(response.uci.unwrap(), "e8c8");
+    }
+
+    #[test]
+    fn validate_castling_without_rights() {
+        let fen = "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w kq - 0 1";
+        let response = validate(fen, "e1", "g1", None);
+        assert!(!response.is_valid);
+        assert_eq!(response.error.unwrap(), "Illegal move");
+    }
+
+    #[test]
+    fn validate_castling_through_check() {
+        // Black bishop on c4 covers f1
+        let fen = "r3k2r/pppppppp/8/8/2b5/8/PPPP1PPP/R3K2R w KQkq - 0 1";
+        let response = validate(fen, "e1", "g1", None);
+        assert!(!response.is_valid);
+
+        // Queenside is still fine
+        let response = validate(fen, "e1", "c1", None);
+        assert!(response.is_valid);
+    }
+
+    #[test]
+    fn validate_en_passant() {
+        // After 1. e4 a6 2. e5 d5 white may capture en passant on d6
+        let fen = "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
+        let response = validate(fen, "e5", "d6", None);
+        assert!(response.is_valid);
+        assert_eq!(response.uci.unwrap(), "e5d6");
+        assert_eq!(response.resulting_fen.unwrap(), "rnbqkbnr/1pp1pppp/p2P4/8/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3");
+    }
+
+    #[test]
+    fn validate_en_passant_expired() {
+        // Same structure but the double step was not the last move
+        let fen = "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3";
+        let response = validate(fen, "e5", "d6", None);
+        assert!(!response.is_valid);
+    }
+
+    #[test]
+    fn validate_black_en_passant() {
+        let fen = "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3";
+        let response = validate(fen, "d4", "e3", None);
+        assert!(response.is_valid);
+        assert_eq!(response.resulting_fen.unwrap(), "rnbqkbnr/ppp1pppp/8/8/8/4p3/PPPP1PPP/RNBQKBNR w KQkq - 0 4");
+    }
+
+    #[test]
+    fn validate_capture_promotion_and_underpromotion() {
+        let fen = "1r5k/P7/8/8/8/8/8/4K3 w - - 0 1";
+        let response = validate(fen, "a7", "b8", Some("n"));
+        assert!(response.is_valid);
+        assert_eq!(response.uci.unwrap(), "a7b8n");
+        assert_eq!(response.resulting_fen.unwrap(), "1N5k/8/8/8/8/8/8/4K3 b - - 0 1");
+
+        // Uppercase promotion pieces are accepted as well
+        let response = validate(fen, "a7", "a8", Some("Q"));
+        assert!(response.is_valid);
+        assert_eq!(response.uci.unwrap(), "a7a8q");
+    }
+
+    #[test]
+    fn validate_promotion_requires_piece() {
+        let fen = "7k/5P2/8/8/8/8/8/4K3 w - - 0 1";
+        let response = validate(fen, "f7", "f8", None);
+        assert!(!response.is_valid);
+        assert_eq!(response.error.unwrap(), "Promotion piece required");
+
+        // A promotion piece on a non-promoting move is rejected
+        let response = validate(fen, "e1", "e2", Some("q"));
+        assert!(!response.is_valid);
+    }
+
+    #[test]
+    fn make_move_castles_on_chain() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+
+        // 1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. O-O (sent as king-to-rook)
+        let moves = [
+            (&white, "e2", "e4"), (&black, "e7", "e5"),
+            (&white, "g1", "f3"), (&black, "b8", "c6"),
+            (&white, "f1", "c4"), (&black, "f8", "c5"),
+            (&white, "e1", "h1"),
+        ];
+        for (player, from, to) in moves {
+            execute(deps.as_mut(), mock_env(), message_info(player, &[]), ExecuteMsg::MakeMove {
+                game_id: 1,
+                from: from.to_string(),
+                to: to.to_string(),
+                promotion: None,
+            }).unwrap();
+        }
+
+        let game = load_game(&deps, 1);
+        assert_eq!(stored_moves(&deps, 1), "e2e4,e7e5,g1f3,b8c6,f1c4,f8c5,e1g1");
+        assert_eq!(game.current_fen, "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 5 4");
+    }
+
+    const DAILY: TimeControl = TimeControl::Fischer { initial: 172_800, increment: 600 };
+
+    /// Start a game the only way the contract allows: `creator` challenges `opponent`, who
+    /// accepts. Returns the response of the acceptance.
+    #[allow(clippy::too_many_arguments)]
+    fn start_game(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, env: Env, creator: &Addr, opponent: &Addr, color: ColorChoice, time_control: TimeControl, clock_unit: Option<ClockUnit>, rated: bool) -> Result<Response, ContractError> {
+        let res = execute(deps.as_mut(), env.clone(), message_info(creator, &[]), ExecuteMsg::CreateChallenge {
+            opponent: Some(opponent.to_string()),
+            color,
+            time_control,
+            clock_unit,
+            rated,
+            color_commitment: None,
+        })?;
+        let challenge_id = attribute(&res, "challenge_id").parse().unwrap();
+        execute(deps.as_mut(), env, message_info(opponent, &[]), ExecuteMsg::AcceptChallenge { challenge_id, entropy: None })
+    }
+
+    /// Rated game with `white` playing white
+    fn create_game(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, white: &Addr, black: &Addr, time_control: TimeControl) -> Result<Response, ContractError> {
+        start_game(deps, mock_env(), white, black, ColorChoice::White, time_control, None, true)
+    }
+
+    fn setup_players(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) -> (Addr, Addr) {
+        let white = deps.api.addr_make("white");
+        let black = deps.api.addr_make("black");
+        let admin = deps.api.addr_make("admin").to_string();
+        instantiate(deps.as_mut(), mock_env(), message_info(&white, &[]), InstantiateMsg { admin: Some(admin), time_control_bounds: None, avg_block_time_ms: None, rating_system: None, house_fee_bps: None, treasury: None, cw20_allowlist: None, nft_contract: None }).unwrap();
+        (white, black)
+    }
+
+    /// Instantiate and start game 1 with a daily time control
+    fn setup_game(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) -> (Addr, Addr) {
+        let (white, black) = setup_players(deps);
+        create_game(deps, &white, &black, DAILY).unwrap();
+        (white, black)
+    }
+
+    /// Packed moves of a game as comma separated UCI
+    fn stored_moves(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, game_id: u64) -> String {
+        load_moves(deps.as_ref().storage, game_id).unwrap().iter().map(|uci| uci.to_string()).collect::<Vec<_>>().join(",")
+    }
+
+    fn load_game(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, game_id: u64) -> ChessGame {
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGame { game_id }).unwrap();
+        from_json::<GameResponse>(&res).unwrap().game.unwrap()
+    }
+
+    #[test]
+    fn game_state_serializes_to_closed_set() {
+        assert_eq!(to_json_string(&GameState::Active).unwrap(), r#""active""#);
+        assert_eq!(
+            to_json_string(&GameState::WhiteWon { reason: Termination::Checkmate }).unwrap(),
+            r#"{"white_won":{"reason":"checkmate"}}"#
+        );
+        assert_eq!(to_json_string(&Color::Black).unwrap(), r#""black""#);
+    }
+
+    #[test]
+    fn resignation_sets_typed_status() {
+        let mut deps = mock_dependencies();
+        let (white, _black) = setup_game(&mut deps);
+
+        let game = load_game(&deps, 1);
+        assert_eq!(game.status, GameState::Active);
+        assert_eq!(game.current_turn, Color::White);
+
+        execute(deps.as_mut(), mock_env(), message_info(&white, &[]), ExecuteMsg::ResignGame {
+            game_id: 1,
+        }).unwrap();
+
+        let game = load_game(&deps, 1);
+        assert_eq!(game.status, GameState::BlackWon { reason: Termination::Resignation });
+        assert_eq!(game.status.termination(), Some(Termination::Resignation));
+    }
+
+    fn play(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, player: &Addr, game_id: u64, uci: &str) -> Response {
+        execute(deps.as_mut(), mock_env(), message_info(player, &[]), ExecuteMsg::MakeMove {
+            game_id,
+            from: uci[0..2].to_string(),
+            to: uci[2..4].to_string(),
+            promotion: uci.get(4..5).map(|p| p.to_string()),
+        }).unwrap()
+    }
+
+    fn attribute<'a>(res: &'a Response, key: &str) -> &'a str {
+        &res.attributes.iter().find(|a| a.key == key).unwrap().value
+    }
+
+    #[test]
+    fn checkmate_records_termination() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+
+        // Fool's mate
+        play(&mut deps, &white, 1, "f2f3");
+        play(&mut deps, &black, 1, "e7e5");
+        play(&mut deps, &white, 1, "g2g4");
+        let res = play(&mut deps, &black, 1, "d8h4");
+
+        assert_eq!(attribute(&res, "result"), "black_won");
+        assert_eq!(attribute(&res, "termination"), "checkmate");
+        assert_eq!(attribute(&res, "winner"), black.as_str());
+
+        let game = load_game(&deps, 1);
+        assert_eq!(game.status, GameState::BlackWon { reason: Termination::Checkmate });
+        assert_eq!(game.status.termination(), Some(Termination::Checkmate));
+    }
+
+    fn game_pgn(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, game_id: u64) -> String {
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGamePgn { game_id }).unwrap();
+        from_json::<PgnResponse>(&res).unwrap().pgn
+    }
+
+    #[test]
+    fn game_exports_as_pgn() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+        for (player, username) in [(&white, "Magnus"), (&black, "Hikaru \"the\" Nakamura")] {
+            execute(deps.as_mut(), mock_env(), message_info(player, &[]), ExecuteMsg::InitializeUser {
+                username: username.to_string(),
+            }).unwrap();
+        }
+        assert!(game_pgn(&deps, 1).ends_with("[Result \"*\"]\n[TimeControl \"172800+600\"]\n[Termination \"unterminated\"]\n\n*\n"));
+
+        for (player, uci) in [(&white, "e2e4"), (&black, "e7e5"), (&white, "g1f3"), (&black, "b8c6"), (&white, "f1c4"), (&black, "g8f6"), (&white, "e1g1"), (&black, "f6e4")] {
+            play(&mut deps, player, 1, uci);
+        }
+        execute(deps.as_mut(), mock_env(), message_info(&white, &[]), ExecuteMsg::ResignGame { game_id: 1 }).unwrap();
+
+        assert_eq!(game_pgn(&deps, 1), concat!(
+            "[Event \"Rated correspondence game\"]\n",
+            "[Site \"Xion Chess\"]\n",
+            "[Date \"2019.10.23\"]\n",
+            "[Round \"-\"]\n",
+            "[White \"Magnus\"]\n",
+            "[Black \"Hikaru \\\"the\\\" Nakamura\"]\n",
+            "[Result \"0-1\"]\n",
+            "[TimeControl \"172800+600\"]\n",
+            "[Termination \"normal\"]\n",
+            "\n",
+            "1. e4 e5 2. Nf3 Nc6 3. Bc4 Nf6 4. O-O Nxe4 0-1\n",
+        ));
+    }
+
+    fn move_history(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, game_id: u64, start_after: Option<u32>, limit: Option<u32>) -> Vec<MoveHistoryEntry> {
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetMoveHistory { game_id, start_after, limit }).unwrap();
+        from_json::<MoveHistoryResponse>(&res).unwrap().moves
+    }
+
+    #[test]
+    fn moves_are_recorded_per_ply() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+        for (player, uci) in [(&white, "e2e4"), (&black, "e7e5"), (&white, "g1f3"), (&black, "b8c6"), (&white, "f1c4"), (&black, "g8f6"), (&white, "e1h1")] {
+            play(&mut deps, player, 1, uci);
+        }
+
+        let history = move_history(&deps, 1, None, None);
+        assert_eq!(history.len(), 7);
+        assert_eq!(history[0], MoveHistoryEntry {
+            ply: 1,
+            uci: "e2e4".to_string(),
+            san: "e4".to_string(),
+            fen: "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1".to_string(),
+            clock_remaining: Some(172_800),
+            block_height: Some(mock_env().block.height),
+            block_time: Some(mock_env().block.time.seconds()),
+        });
+        // Castling is stored normalized, white got the increment for moves 2 to 4
+        assert_eq!((history[6].uci.as_str(), history[6].san.as_str()), ("e1g1", "O-O"));
+        assert_eq!(history[6].clock_remaining, Some(174_600));
+        assert_eq!(history[6].fen, load_game(&deps, 1).current_fen);
+
+        let page = move_history(&deps, 1, Some(4), Some(2));
+        assert_eq!(page.iter().map(|record| record.san.as_str()).collect::<Vec<_>>(), vec!["Bc4", "Nf6"]);
+        assert!(move_history(&deps, 2, None, None).is_empty());
+    }
+
+    #[test]
+    fn draw_by_agreement_records_termination() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+
+        execute(deps.as_mut(), mock_env(), message_info(&white, &[]), ExecuteMsg::ProposeDrawRequest {
+            game_id: 1,
+        }).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), message_info(&black, &[]), ExecuteMsg::RespondToDrawRequest {
+            game_id: 1,
+            accept: true,
+        }).unwrap();
+
+        assert_eq!(attribute(&res, "result"), "draw");
+        assert_eq!(attribute(&res, "termination"), "agreement");
+        assert_eq!(load_game(&deps, 1).status.termination(), Some(Termination::Agreement));
+    }
+
+    #[test]
+    fn games_can_be_filtered_by_termination() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+        create_game(&mut deps, &white, &black, DAILY).unwrap();
+        execute(deps.as_mut(), mock_env(), message_info(&black, &[]), ExecuteMsg::ResignGame {
+            game_id: 1,
+        }).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ListGames {
+            filter: Some(GameFilter { termination: Some(Termination::Resignation), ..GameFilter::default() }),
+            start_after: None,
+            limit: None,
+            order: None,
+        }).unwrap();
+        let games = from_json::<GamesResponse>(&res).unwrap().games;
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].id, 1);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPlayerGames {
+            player: white.clone(),
+            filter: Some(GameFilter { termination: Some(Termination::Checkmate), ..GameFilter::default() }),
+            start_after: None,
+            limit: None,
+            order: None,
+        }).unwrap();
+        assert!(from_json::<GamesResponse>(&res).unwrap().games.is_empty());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPlayerGames {
+            player: white,
+            filter: None,
+            start_after: None,
+            limit: None,
+            order: None,
+        }).unwrap();
+        assert_eq!(from_json::<GamesResponse>(&res).unwrap().games.len(), 2);
+    }
+
+    #[test]
+    fn game_listings_are_paginated_and_filtered() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+        let carol = deps.api.addr_make("carol");
+        for opponent in [&black, &carol, &black, &carol] {
+            create_game(&mut deps, &white, opponent, TimeControl::Correspondence { per_move: 86_400 }).unwrap();
+        }
+        execute_as(&mut deps, &black, ExecuteMsg::ResignGame { game_id: 4 }).unwrap();
+
+        let list = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, filter: Option<GameFilter>, start_after: Option<u64>, order: Option<SortOrder>| -> Vec<u64> {
+            let res = query(deps.as_ref(), mock_env(), QueryMsg::ListGames { filter, start_after, limit: Some(2), order }).unwrap();
+            from_json::<GamesResponse>(&res).unwrap().games.iter().map(|game| game.id).collect()
+        };
+        assert_eq!(list(&deps, None, None, None), vec![1, 2]);
+        assert_eq!(list(&deps, None, Some(2), None), vec![3, 4]);
+        assert_eq!(list(&deps, None, None, Some(SortOrder::Descending)), vec![5, 4]);
+        assert_eq!(list(&deps, None, Some(4), Some(SortOrder::Descending)), vec![3, 2]);
+
+        let correspondence = GameFilter { time_control: Some(TimeControlKind::Correspondence), ..GameFilter::default() };
+        assert_eq!(list(&deps, Some(correspondence.clone()), Some(2), None), vec![3, 4]);
+        let active = GameFilter { status: Some(StatusFilter::Active), ..correspondence };
+        assert_eq!(list(&deps, Some(active), None, None), vec![2, 3]);
+        let finished = GameFilter { status: Some(StatusFilter::Finished), ..GameFilter::default() };
+        assert_eq!(list(&deps, Some(finished), None, None), vec![4]);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPlayerGames {
+            player: carol,
+            filter: None,
+            start_after: Some(3),
+            limit: None,
+            order: None,
+        }).unwrap();
+        let games = from_json::<GamesResponse>(&res).unwrap().games;
+        assert_eq!(games.iter().map(|game| game.id).collect::<Vec<_>>(), vec![5]);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetAllUsers { start_after: None, limit: Some(1), order: None }).unwrap();
+        let first = from_json::<UsersResponse>(&res).unwrap().users;
+        assert_eq!(first.len(), 1);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetAllUsers { start_after: Some(first[0].to_string()), limit: None, order: None }).unwrap();
+        let rest = from_json::<UsersResponse>(&res).unwrap().users;
+        assert_eq!(rest.len(), 2);
+        assert!(!rest.contains(&first[0]));
+    }
+
+    #[test]
+    fn filtered_listings_scan_a_bounded_number_of_games() {
+        let mut deps = mock_dependencies();
+        let (white, _black) = setup_game(&mut deps);
+        let total = MAX_GAME_SCAN as u64 + 5;
+        let mut game = load_game(&deps, 1);
+        for id in 2..=total {
+            game.id = id;
+            game.time_control = if id == total { TimeControl::Correspondence { per_move: 86_400 } } else { DAILY };
+            GAMES.save(deps.as_mut().storage, id, &game).unwrap();
+        }
+        let correspondence = Some(GameFilter { time_control: Some(TimeControlKind::Correspondence), ..GameFilter::default() });
+        let list = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, start_after: Option<u64>| -> GamesResponse {
+            let res = query(deps.as_ref(), mock_env(), QueryMsg::ListGames { filter: correspondence.clone(), start_after, limit: None, order: None }).unwrap();
+            from_json(&res).unwrap()
+        };
+
+        // The only match lies beyond the scanned games, the cursor leads to it
+        let first = list(&deps, None);
+        assert!(first.games.is_empty());
+        assert_eq!(first.next_start_after, Some(MAX_GAME_SCAN as u64));
+        let second = list(&deps, first.next_start_after);
+        assert_eq!(second.games.iter().map(|game| game.id).collect::<Vec<_>>(), vec![total]);
+        assert_eq!(second.next_start_after, None);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPlayerGames {
+            player: white,
+            filter: correspondence.clone(),
+            start_after: None,
+            limit: None,
+            order: None,
+        }).unwrap();
+        let page: GamesResponse = from_json(&res).unwrap();
+        assert!(page.games.is_empty());
+        assert_eq!(page.next_start_after, Some(MAX_GAME_SCAN as u64));
+    }
+
+    #[test]
+    fn player_games_merge_both_colors() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+        let carol = deps.api.addr_make("carol");
+        create_game(&mut deps, &black, &white, DAILY).unwrap();
+        create_game(&mut deps, &black, &carol, DAILY).unwrap();
+        create_game(&mut deps, &white, &black, DAILY).unwrap();
+
+        let player_games = |order: SortOrder, start_after: Option<u64>| -> Vec<u64> {
+            let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPlayerGames {
+                player: white.clone(),
+                filter: None,
+                start_after,
+                limit: None,
+                order: Some(order),
+            }).unwrap();
+            from_json::<GamesResponse>(&res).unwrap().games.iter().map(|game| game.id).collect()
+        };
+        assert_eq!(player_games(SortOrder::Ascending, None), vec![1, 2, 4]);
+        assert_eq!(player_games(SortOrder::Descending, None), vec![4, 2, 1]);
+        assert_eq!(player_games(SortOrder::Descending, Some(4)), vec![2, 1]);
+    }
+
+    #[test]
+    fn migrated_games_are_indexed() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_players(&mut deps);
+        let legacy = LegacyChessGame {
+            id: "a".to_string(),
+            white: white.clone(),
+            black,
+            moves: String::new(),
+            current_fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string(),
+            status: "active".to_string(),
+            current_turn: "white".to_string(),
+            last_move_block: 10,
+            white_time_remaining: 172_800,
+            black_time_remaining: 172_800,
+            created_block: 5,
+            claim_block: None,
+            time_control: "daily".to_string(),
+            move_count: 0,
+            draw_proposed_by: None,
+        };
+        LEGACY_GAMES.save(deps.as_mut().storage, "a".to_string(), &legacy).unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPlayerGames {
+            player: white,
+            filter: None,
+            start_after: None,
+            limit: None,
+            order: None,
+        }).unwrap();
+        assert_eq!(from_json::<GamesResponse>(&res).unwrap().games.len(), 1);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ListGames {
+            filter: Some(GameFilter { status: Some(StatusFilter::Active), ..GameFilter::default() }),
+            start_after: None,
+            limit: None,
+            order: None,
+        }).unwrap();
+        assert_eq!(from_json::<GamesResponse>(&res).unwrap().games.len(), 1);
+    }
+
+    #[test]
+    fn migrate_converts_string_status_games() {
+        let mut deps = mock_dependencies();
+        let white = deps.api.addr_make("white");
+        let black = deps.api.addr_make("black");
+
+        let legacy = |id: &str, status: &str, turn: &str| LegacyChessGame {
+            id: id.to_string(),
+            white: white.clone(),
+            black: black.clone(),
+            moves: "e2e4".to_string(),
+            current_fen: "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1".to_string(),
+            status: status.to_string(),
+            current_turn: turn.to_string(),
+            last_move_block: 10,
+            white_time_remaining: 172_800,
+            black_time_remaining: 172_800,
+            created_block: 5,
+            claim_block: None,
+            time_control: "daily".to_string(),
+            move_count: 1,
+            draw_proposed_by: None,
+        };
+        for (id, status, turn) in [("a", "active", "black"), ("b", "white_won", "black"), ("c", "draw", "white"), ("d", "disputed", "white")] {
+            LEGACY_GAMES.save(deps.as_mut().storage, id.to_string(), &legacy(id, status, turn)).unwrap();
+            LEGACY_GAME_INDEX.save(deps.as_mut().storage, id.to_string(), &true).unwrap();
+        }
+        let legacy_profile = LegacyUserProfile {
+            username: "white".to_string(),
+            elo: 1250,
+            games_played: 3,
+            wins: 2,
+            draws: 0,
+            losses: 1,
+            current_games: vec!["a".to_string()],
+            created_at: 1,
+        };
+        LEGACY_USER_PROFILES.save(deps.as_mut().storage, white.clone(), &legacy_profile).unwrap();
+        USER_ADDRESSES.save(deps.as_mut().storage, white.clone(), &true).unwrap();
+
+        // Contracts without a config need an admin to migrate
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+        assert!(matches!(err, ContractError::AdminRequired {}));
+
+        let admin = deps.api.addr_make("admin").to_string();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: Some(admin) }).unwrap();
+        assert_eq!(res.attributes[1].value, "4");
+        // Repetition tracking is rebuilt for the active game
+        assert_eq!(res.attributes[2].value, "1");
+        assert_eq!(res.attributes[3].value, "1");
+        // Every game gets its move replayed into the move history
+        assert_eq!(res.attributes[4].value, "4");
+        let history = move_history(&deps, 1, None, None);
+        assert_eq!(history.len(), 1);
+        assert_eq!(stored_moves(&deps, 1), "e2e4");
+        assert_eq!((history[0].san.as_str(), history[0].clock_remaining, history[0].block_height), ("e4", None, None));
+
+        // Games get contract ids in the order of their old ids, which stay resolvable
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGameByLegacyId { legacy_id: "c".to_string() }).unwrap();
+        assert_eq!(from_json::<GameResponse>(&res).unwrap().game.unwrap().id, 3);
+        assert!(LEGACY_GAMES.is_empty(deps.as_ref().storage));
+        assert!(LEGACY_GAME_INDEX.is_empty(deps.as_ref().storage));
+        let profile = USER_PROFILES.load(deps.as_ref().storage, white.clone()).unwrap();
+        assert_eq!(profile.current_games, vec![1]);
+        assert_eq!(profile.elo, 1250);
+        assert_eq!(profile.peak_elo, 1250);
+
+        let game = load_game(&deps, 1);
+        assert_eq!(game.status, GameState::Active);
+        assert_eq!(game.current_turn, Color::Black);
+        assert_eq!(game.status.termination(), None);
+        assert_eq!(game.time_control, TimeControl::Fischer { initial: 172_800, increment: 600 });
+        // Games past move 20 already had their increment cut to 60 blocks
+        let late = LegacyChessGame { move_count: 21, ..legacy("e", "active", "white") }.into_game(5, 0);
+        assert_eq!(late.time_control, TimeControl::Fischer { initial: 172_800, increment: 60 });
+        // Last moved at block 10, one second per block
+        let block = mock_env().block;
+        assert_eq!(game.last_move_time, block.time.seconds() - (block.height - 10));
+        let game = load_game(&deps, 2);
+        assert_eq!(game.status, GameState::WhiteWon { reason: Termination::Unknown });
+        assert_eq!(game.status.termination(), Some(Termination::Unknown));
+        assert_eq!(load_game(&deps, 3).status, GameState::Draw { reason: Termination::Unknown });
+        let game = load_game(&deps, 4);
+        assert_eq!(game.status, GameState::Aborted);
+        assert_eq!(game.status.termination(), Some(Termination::Aborted));
+
+        // Running the migration again is a no-op
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+        assert_eq!(res.attributes[1].value, "0");
+        assert_eq!(res.attributes[2].value, "0");
+        assert_eq!(res.attributes[3].value, "0");
+    }
+
+    fn env_at(height: u64) -> Env {
+        let mut env = mock_env();
+        env.block.height = height;
+        env
+    }
+
+    fn execute_as(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, sender: &Addr, msg: ExecuteMsg) -> Result<Response, ContractError> {
+        execute(deps.as_mut(), mock_env(), message_info(sender, &[]), msg)
+    }
+
+    #[test]
+    fn claim_timeout_is_checked_against_the_clock() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+        let claim = ExecuteMsg::ClaimTimeout { game_id: 1 };
+        let start = mock_env().block.height;
+
+        // Clock only starts once both players have moved
+        let err = execute_as(&mut deps, &black, claim.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::ClockNotStarted {}));
+
+        play(&mut deps, &white, 1, "e2e4");
+        play(&mut deps, &black, 1, "e7e5");
+
+        // White is to move, so only black can claim
+        let err = execute_as(&mut deps, &white, claim.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::CannotClaimOwnTimeout {}));
+
+        let err = execute(deps.as_mut(), env_at(start + 1_000), message_info(&black, &[]), claim.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::TimeNotExpired { remaining: 171_800, unit: ClockUnit::Blocks }));
+
+        let res = execute(deps.as_mut(), env_at(start + 172_800), message_info(&black, &[]), claim).unwrap();
+        assert_eq!(attribute(&res, "termination"), "timeout");
+        assert_eq!(load_game(&deps, 1).status, GameState::BlackWon { reason: Termination::Timeout });
+    }
+
+    #[test]
+    fn claim_threefold_repetition() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+        let claim = ExecuteMsg::ClaimDraw { game_id: 1, reason: DrawClaim::ThreefoldRepetition };
+
+        for _ in 0..2 {
+            let err = execute_as(&mut deps, &white, claim.clone()).unwrap_err();
+            assert!(matches!(err, ContractError::NoThreefoldRepetition { .. }));
+            play(&mut deps, &white, 1, "g1f3");
+            play(&mut deps, &black, 1, "g8f6");
+            play(&mut deps, &white, 1, "f3g1");
+            play(&mut deps, &black, 1, "f6g8");
+        }
+
+        // The starting position has now occurred three times
+        let res = execute_as(&mut deps, &black, claim).unwrap();
+        assert_eq!(attribute(&res, "result"), "draw");
+        assert_eq!(load_game(&deps, 1).status.termination(), Some(Termination::ThreefoldRepetition));
+    }
+
+    #[test]
+    fn position_counts_are_dropped_when_positions_cannot_repeat() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+        let tracked = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>| POSITION_COUNTS
+            .prefix(1)
+            .keys(&deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .count();
+
+        play(&mut deps, &white, 1, "g1f3");
+        play(&mut deps, &black, 1, "g8f6");
+        assert_eq!(tracked(&deps), 3);
+
+        // A pawn move makes every earlier position unreachable
+        play(&mut deps, &white, 1, "e2e4");
+        assert_eq!(tracked(&deps), 1);
+
+        execute_as(&mut deps, &black, ExecuteMsg::ResignGame { game_id: 1 }).unwrap();
+        assert_eq!(tracked(&deps), 0);
+    }
+
+    #[test]
+    fn claim_fifty_move_rule() {
+        let mut deps = mock_dependencies();
+        let (white, _black) = setup_game(&mut deps);
+        let claim = ExecuteMsg::ClaimDraw { game_id: 1, reason: DrawClaim::FiftyMoveRule };
+
+        let err = execute_as(&mut deps, &white, claim.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::FiftyMoveRuleNotReached { halfmoves: 0 }));
+
+        let mut game = load_game(&deps, 1);
+        game.current_fen = "8/8/4k3/8/8/3RK3/8/8 w - - 100 80".to_string();
+        GAMES.save(deps.as_mut().storage, 1, &game).unwrap();
+
+        execute_as(&mut deps, &white, claim).unwrap();
+        assert_eq!(load_game(&deps, 1).status, GameState::Draw { reason: Termination::FiftyMoveRule });
+    }
+
+    #[test]
+    fn game_ids_are_assigned_by_the_contract() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+
+        let res = create_game(&mut deps, &white, &black, DAILY).unwrap();
+        let created: GameCreatedResponse = from_json(res.data.as_ref().unwrap()).unwrap();
+        assert_eq!(created.game_id, 2);
+        assert_eq!(attribute(&res, "game_id"), "2");
+        assert_eq!(load_game(&deps, 2).id, 2);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetAllGameIds { start_after: None, limit: None, order: None }).unwrap();
+        assert_eq!(from_json::<GameIdsResponse>(&res).unwrap().game_ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn creator_can_choose_color() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_players(&mut deps);
+
+        let res = start_game(&mut deps, mock_env(), &white, &black, ColorChoice::Black, DAILY, None, true).unwrap();
+        assert_eq!(attribute(&res, "challenger_color"), "black");
+        assert_eq!(load_game(&deps, 1).black, white);
+    }
+
+    /// Random color challenge from `creator` committed to `secret` and accepted by `opponent`
+    fn accept_random_challenge(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, creator: &Addr, opponent: &Addr, secret: &[u8], entropy: &[u8]) -> u64 {
+        let res = execute_as(deps, creator, ExecuteMsg::CreateChallenge {
+            opponent: Some(opponent.to_string()),
+            color: ColorChoice::Random,
+            time_control: DAILY,
+            clock_unit: None,
+            rated: true,
+            color_commitment: Some(Binary::from(Sha256::digest(secret).to_vec())),
+        }).unwrap();
+        let challenge_id = attribute(&res, "challenge_id").parse().unwrap();
+        execute_as(deps, opponent, ExecuteMsg::AcceptChallenge { challenge_id, entropy: Some(Binary::from(entropy)) }).unwrap();
+        challenge_id
+    }
+
+    #[test]
+    fn random_color_is_settled_by_the_revealed_secret() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_players(&mut deps);
+
+        // The challenge waits for the reveal, neither side can back out
+        let challenge_id = accept_random_challenge(&mut deps, &white, &black, b"secret", b"entropy");
+        assert!(GAMES.is_empty(deps.as_ref().storage));
+        let err = execute_as(&mut deps, &white, ExecuteMsg::CancelChallenge { challenge_id }).unwrap_err();
+        assert!(matches!(err, ContractError::ChallengeAwaitsReveal {}));
+        let err = execute_as(&mut deps, &black, ExecuteMsg::DeclineChallenge { challenge_id }).unwrap_err();
+        assert!(matches!(err, ContractError::ChallengeAwaitsReveal {}));
+
+        let res = execute_as(&mut deps, &white, ExecuteMsg::RevealColor { challenge_id, secret: Binary::from(b"secret") }).unwrap();
+        let created: GameCreatedResponse = from_json(res.data.as_ref().unwrap()).unwrap();
+        assert_eq!(attribute(&res, "color_choice"), "random");
+        let challenger_color = random_color(b"secret", b"entropy");
+        assert_eq!(attribute(&res, "challenger_color"), challenger_color.as_str());
+        let game = load_game(&deps, created.game_id);
+        assert_eq!(game.white == white, challenger_color == Color::White);
+
+        // Different secrets land on both colors
+        let mut picked = Vec::new();
+        for secret in 0..16u8 {
+            let challenge_id = accept_random_challenge(&mut deps, &white, &black, &[secret], b"entropy");
+            let res = execute_as(&mut deps, &white, ExecuteMsg::RevealColor { challenge_id, secret: Binary::from([secret]) }).unwrap();
+            picked.push(attribute(&res, "challenger_color").to_string());
+        }
+        assert!(picked.contains(&"white".to_string()) && picked.contains(&"black".to_string()));
+    }
+
+    #[test]
+    fn random_color_needs_a_matching_reveal_in_time() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_players(&mut deps);
+        let create = |color, color_commitment| ExecuteMsg::CreateChallenge {
+            opponent: Some(black.to_string()),
+            color,
+            time_control: DAILY,
+            clock_unit: None,
+            rated: true,
+            color_commitment,
+        };
+        let commitment = Some(Binary::from(Sha256::digest(b"secret").to_vec()));
+        for (color, commitment) in [(ColorChoice::Random, None), (ColorChoice::Random, Some(Binary::from(b"short"))), (ColorChoice::White, commitment.clone())] {
+            let err = execute_as(&mut deps, &white, create(color, commitment)).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidColorCommitment {}));
+        }
+        execute_as(&mut deps, &white, create(ColorChoice::Random, commitment)).unwrap();
+        let err = execute_as(&mut deps, &black, ExecuteMsg::AcceptChallenge { challenge_id: 1, entropy: None }).unwrap_err();
+        assert!(matches!(err, ContractError::EntropyRequired {}));
+        let err = execute_as(&mut deps, &white, ExecuteMsg::RevealColor { challenge_id: 1, secret: Binary::from(b"secret") }).unwrap_err();
+        assert!(matches!(err, ContractError::NoPendingReveal {}));
+
+        let challenge_id = accept_random_challenge(&mut deps, &white, &black, b"secret", b"entropy");
+        let err = execute_as(&mut deps, &white, ExecuteMsg::RevealColor { challenge_id, secret: Binary::from(b"guess") }).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidReveal {}));
+        let deadline = mock_env().block.height + COLOR_REVEAL_BLOCKS;
+        let err = execute_as(&mut deps, &black, ExecuteMsg::ClaimRevealTimeout { challenge_id }).unwrap_err();
+        assert!(matches!(err, ContractError::RevealDeadlineNotReached { deadline: d } if d == deadline));
+
+        // Past the deadline the secret is no longer accepted and the opponent takes the challenge
+        let reveal = ExecuteMsg::RevealColor { challenge_id, secret: Binary::from(b"secret") };
+        let err = execute(deps.as_mut(), env_at(deadline + 1), message_info(&white, &[]), reveal).unwrap_err();
+        assert!(matches!(err, ContractError::RevealDeadlinePassed {}));
+        let claim = ExecuteMsg::ClaimRevealTimeout { challenge_id };
+        let err = execute(deps.as_mut(), env_at(deadline + 1), message_info(&white, &[]), claim.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NotChallengeRecipient {}));
+        execute(deps.as_mut(), env_at(deadline + 1), message_info(&black, &[]), claim).unwrap();
+        assert!(GAMES.is_empty(deps.as_ref().storage));
+        assert!(query(deps.as_ref(), mock_env(), QueryMsg::GetChallenge { challenge_id }).is_err());
+    }
+
+    #[test]
+    fn abort_only_before_both_players_moved() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+        create_game(&mut deps, &white, &black, DAILY).unwrap();
+
+        play(&mut deps, &white, 1, "e2e4");
+        execute_as(&mut deps, &black, ExecuteMsg::AbortGame { game_id: 1 }).unwrap();
+        let game = load_game(&deps, 1);
+        assert_eq!(game.status, GameState::Aborted);
+        assert_eq!(game.status.termination(), Some(Termination::Aborted));
+
+        play(&mut deps, &white, 2, "e2e4");
+        play(&mut deps, &black, 2, "e7e5");
+        let err = execute_as(&mut deps, &white, ExecuteMsg::AbortGame { game_id: 2 }).unwrap_err();
+        assert!(matches!(err, ContractError::AbortNotAllowed {}));
+    }
+
+    #[test]
+    fn only_admin_can_adjudicate() {
+        let mut deps = mock_dependencies();
+        let (white, _black) = setup_game(&mut deps);
+        let admin = deps.api.addr_make("admin");
+        let result = GameState::WhiteWon { reason: Termination::Adjudication };
+
+        let err = execute_as(&mut deps, &white, ExecuteMsg::AdjudicateGame { game_id: 1, result }).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let err = execute_as(&mut deps, &admin, ExecuteMsg::AdjudicateGame { game_id: 1, result: GameState::Active }).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidAdjudication {}));
+
+        execute_as(&mut deps, &admin, ExecuteMsg::AdjudicateGame { game_id: 1, result }).unwrap();
+        assert_eq!(load_game(&deps, 1).status, result);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetUserProfile { address: white }).unwrap();
+        assert_eq!(from_json::<UserProfileResponse>(&res).unwrap().profile.unwrap().wins, 1);
+    }
+
+    fn leaderboard_page(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, start_after: Option<LeaderboardCursor>, include_provisional: bool, active_since: Option<u64>, category: Option<RatingCategory>) -> LeaderboardResponse {
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Leaderboard {
+            start_after,
+            limit: Some(2),
+            include_provisional: Some(include_provisional),
+            active_since,
+            category,
+        }).unwrap();
+        from_json(&res).unwrap()
+    }
+
+    fn ranks(page: &LeaderboardResponse) -> Vec<(u32, String, u32)> {
+        page.entries.iter()
+            .map(|entry| (entry.rank, entry.username.clone(), entry.rating))
+            .collect()
+    }
+
+    fn leaderboard(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, start_after: Option<LeaderboardCursor>, include_provisional: bool, active_since: Option<u64>, category: Option<RatingCategory>) -> Vec<(u32, String, u32)> {
+        ranks(&leaderboard_page(deps, start_after, include_provisional, active_since, category))
+    }
+
+    #[test]
+    fn leaderboard_ranks_players_by_rating() {
+        let mut deps = mock_dependencies();
+        setup_players(&mut deps);
+        for (name, elo, games_played, last_game_at) in [("ann", 1500, 40, 100), ("bob", 1800, 5, 100), ("cat", 1650, 30, 10), ("dan", 1400, 60, 100)] {
+            let profile = UserProfile { username: name.to_string(), elo, games_played, last_game_at, ..UserProfile::default() };
+            USER_PROFILES.save(&mut deps.storage, deps.api.addr_make(name), &profile).unwrap();
+        }
+
+        let first = leaderboard_page(&deps, None, true, None, None);
+        assert_eq!(ranks(&first), vec![(1, "bob".to_string(), 1800), (2, "cat".to_string(), 1650)]);
+        let second = leaderboard_page(&deps, first.next_start_after, true, None, None);
+        assert_eq!(ranks(&second), vec![(3, "ann".to_string(), 1500), (4, "dan".to_string(), 1400)]);
+        assert!(leaderboard(&deps, second.next_start_after, true, None, None).is_empty());
+        // Provisional players are left out by default, ranks continue across pages
+        let first = leaderboard_page(&deps, None, false, None, None);
+        assert_eq!(ranks(&first), vec![(1, "cat".to_string(), 1650), (2, "ann".to_string(), 1500)]);
+        let second = leaderboard_page(&deps, first.next_start_after, false, None, None);
+        assert_eq!(ranks(&second), vec![(3, "dan".to_string(), 1400)]);
+        assert_eq!(second.next_start_after, None);
+        assert_eq!(leaderboard(&deps, None, false, Some(50), None), vec![(1, "ann".to_string(), 1500), (2, "dan".to_string(), 1400)]);
+
+        // Finished games move players on the leaderboard
+        let ann = deps.api.addr_make("ann");
+        let dan = deps.api.addr_make("dan");
+        create_game(&mut deps, &dan, &ann, DAILY).unwrap();
+        play(&mut deps, &dan, 1, "f2f3");
+        play(&mut deps, &ann, 1, "e7e5");
+        play(&mut deps, &dan, 1, "g2g4");
+        execute_as(&mut deps, &ann, ExecuteMsg::ResignGame { game_id: 1 }).unwrap();
+        let top = leaderboard(&deps, None, false, Some(mock_env().block.height), None);
+        assert_eq!(top.iter().map(|(_, name, _)| name.as_str()).collect::<Vec<_>>(), vec!["ann", "dan"]);
+        assert!(top[0].2 < 1500);
+        assert!(top[1].2 > 1400);
+    }
+
+    #[test]
+    fn elo_expected_score_matches_logistic_curve() {
+        // 1 / (1 + 10^(-d / 400)) to six decimals, in fixed-point
+        for (diff, expected) in [(0, 500_000_000), (26, 537_347_000), (50, 571_463_000), (100, 640_065_000), (200, 759_747_000), (400, 909_091_000), (-400, 90_909_000), (800, 990_099_000)] {
+            let actual = elo_expected(diff).unwrap();
+            assert!((actual - expected).abs() < 1_000, "diff {diff}: {actual} != {expected}");
+        }
+        // Both sides always add up to one
+        assert!((elo_expected(1234).unwrap() + elo_expected(-1234).unwrap() - 1_000_000_000).abs() <= 1);
+    }
+
+    #[test]
+    fn elo_uses_fide_k_factors() {
+        assert_eq!(elo_update(1500, 1500, 1000, 20).unwrap(), 1510);
+        assert_eq!(elo_update(2000, 1800, 1000, 20).unwrap(), 2005);
+        assert_eq!(elo_update(2000, 1800, 0, 20).unwrap(), 1985);
+        assert_eq!(elo_update(1800, 2000, 500, 20).unwrap(), 1805);
+        assert_eq!(elo_update(1200, 1200, 1000, 40).unwrap(), 1220);
+
+        let new_player = UserProfile::default();
+        assert_eq!(new_player.k_factor(), 40);
+        let established = UserProfile { games_played: 30, elo: 2350, peak_elo: 2350, ..UserProfile::default() };
+        assert_eq!(established.k_factor(), 20);
+        // Once 2400 is reached K stays at 10, even after dropping below
+        let master = UserProfile { peak_elo: 2410, ..established };
+        assert_eq!(master.k_factor(), 10);
+    }
+
+    #[test]
+    fn glicko2_matches_reference_example() {
+        // Worked example from Glickman's "Example of the Glicko-2 system"
+        let player = Glicko2 { rating: 1500, deviation: 200, volatility: 60_000 };
+        let results = [
+            (Glicko2 { rating: 1400, deviation: 30, volatility: 60_000 }, 1000),
+            (Glicko2 { rating: 1550, deviation: 100, volatility: 60_000 }, 0),
+            (Glicko2 { rating: 1700, deviation: 300, volatility: 60_000 }, 0),
+        ];
+        // 1464.06, 151.52 and 0.05999
+        assert_eq!(glicko2_update(player, &results).unwrap(), Glicko2 { rating: 1464, deviation: 152, volatility: 59_996 });
+    }
+
+    #[test]
+    fn rating_math_handles_extreme_ratings() {
+        assert_eq!(elo_update(100, 4000, 1000, 40).unwrap(), 140);
+        assert_eq!(elo_update(4000, 100, 0, 40).unwrap(), 3960);
+        assert_eq!(elo_update(4000, 100, 1000, 10).unwrap(), 4000);
+        assert!(ln(0).is_err());
+
+        // Zero deviations and volatilities are clamped instead of dividing by zero
+        let weak = Glicko2 { rating: 100, deviation: 0, volatility: 0 };
+        let strong = Glicko2 { rating: 4000, deviation: 0, volatility: 0 };
+        let upset = glicko2_update(weak, &[(strong, 1000)]).unwrap();
+        assert!(upset.rating > 100 && upset.deviation > 0 && upset.volatility > 0);
+        let upset = glicko2_update(strong, &[(weak, 0)]).unwrap();
+        assert!(upset.rating < 4000);
+        let expected = glicko2_update(strong, &[(weak, 1000)]).unwrap();
+        assert!(expected.rating >= 4000 && expected.volatility > 0);
+        assert!(glicko2_update(weak, &[]).unwrap().deviation >= 30);
+    }
+
+    #[test]
+    fn glicko2_can_be_selected_in_config() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+        let admin = deps.api.addr_make("admin");
+        execute_as(&mut deps, &admin, ExecuteMsg::UpdateConfig { admin: None, time_control_bounds: None, avg_block_time_ms: None, rating_system: Some(RatingSystem::Glicko2), house_fee_bps: None, treasury: None, clear_treasury: false, cw20_allowlist: None, nft_contract: None }).unwrap();
+
+        play(&mut deps, &white, 1, "e2e4");
+        play(&mut deps, &black, 1, "e7e5");
+        execute_as(&mut deps, &black, ExecuteMsg::ResignGame { game_id: 1 }).unwrap();
+
+        let winner = USER_PROFILES.load(&deps.storage, white).unwrap();
+        let loser = USER_PROFILES.load(&deps.storage, black).unwrap();
+        // Two fresh 1200 players with deviation 350 move by about 162 points
+        assert_eq!((winner.elo, winner.deviation), (1362, 290));
+        assert_eq!((loser.elo, loser.deviation), (1038, 290));
+        assert_eq!(winner.volatility, loser.volatility);
+    }
+
+    #[test]
+    fn rating_category_follows_time_control() {
+        let category = |time_control: TimeControl, clock_unit: ClockUnit| query::rating_category(&time_control, clock_unit, 2_000);
+        assert_eq!(category(TimeControl::Fischer { initial: 60, increment: 1 }, ClockUnit::Seconds), RatingCategory::Bullet);
+        assert_eq!(category(TimeControl::Fischer { initial: 180, increment: 2 }, ClockUnit::Seconds), RatingCategory::Blitz);
+        assert_eq!(category(TimeControl::Bronstein { initial: 600, delay: 5 }, ClockUnit::Seconds), RatingCategory::Rapid);
+        assert_eq!(category(TimeControl::Fischer { initial: 1_800, increment: 0 }, ClockUnit::Seconds), RatingCategory::Classical);
+        assert_eq!(category(TimeControl::Fischer { initial: 86_400, increment: 0 }, ClockUnit::Seconds), RatingCategory::Correspondence);
+        // 300 blocks of two seconds are ten minutes
+        assert_eq!(category(TimeControl::Fischer { initial: 300, increment: 0 }, ClockUnit::Blocks), RatingCategory::Rapid);
+        assert_eq!(category(TimeControl::Correspondence { per_move: 600 }, ClockUnit::Seconds), RatingCategory::Correspondence);
+        assert_eq!(category(TimeControl::Unlimited, ClockUnit::Blocks), RatingCategory::Correspondence);
+    }
+
+    #[test]
+    fn ratings_are_tracked_per_category() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+        start_game(&mut deps, mock_env(), &black, &white, ColorChoice::White, TimeControl::Fischer { initial: 180, increment: 2 }, Some(ClockUnit::Seconds), true).unwrap();
+        assert_eq!(load_game(&deps, 1).category, RatingCategory::Correspondence);
+        assert_eq!(load_game(&deps, 2).category, RatingCategory::Blitz);
+
+        // Black wins the daily game, then also the blitz game playing white
+        execute_as(&mut deps, &white, ExecuteMsg::ResignGame { game_id: 1 }).unwrap();
+        execute_as(&mut deps, &white, ExecuteMsg::ResignGame { game_id: 2 }).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetUserProfile { address: black.clone() }).unwrap();
+        let response: UserProfileResponse = from_json(&res).unwrap();
+        let profile = response.profile.unwrap();
+        assert_eq!((profile.games_played, profile.wins), (2, 2));
+        assert_eq!(response.ratings.iter().map(|r| (r.category, r.rating.games_played, r.rating.wins, r.rating.elo)).collect::<Vec<_>>(), vec![
+            (RatingCategory::Blitz, 1, 1, 1220),
+            (RatingCategory::Correspondence, 1, 1, 1220),
+        ]);
+        assert!(profile.elo > 1220);
+
+        let blitz = leaderboard(&deps, None, true, None, Some(RatingCategory::Blitz));
+        assert_eq!(blitz, vec![(1, black.to_string(), 1220), (2, white.to_string(), 1180)]);
+        assert!(leaderboard(&deps, None, true, None, Some(RatingCategory::Bullet)).is_empty());
+    }
+
+    #[test]
+    fn casual_games_leave_ratings_untouched() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_players(&mut deps);
+        let res = start_game(&mut deps, mock_env(), &white, &black, ColorChoice::White, DAILY, None, false).unwrap();
+        assert_eq!(attribute(&res, "rated"), "false");
+        assert!(!load_game(&deps, 1).rated);
+        execute_as(&mut deps, &black, ExecuteMsg::ResignGame { game_id: 1 }).unwrap();
+
+        let winner = USER_PROFILES.load(&deps.storage, white.clone()).unwrap();
+        assert_eq!((winner.elo, winner.games_played, winner.casual_games, winner.wins), (1200, 1, 1, 1));
+        let loser = USER_PROFILES.load(&deps.storage, black.clone()).unwrap();
+        assert_eq!((loser.elo, loser.games_played, loser.casual_games, loser.losses), (1200, 1, 1, 1));
+        let category = category_ratings(RatingCategory::Correspondence).load(&deps.storage, white.clone()).unwrap();
+        assert_eq!((category.elo, category.wins, category.casual_games), (1200, 1, 1));
+
+        // Casual games don't count towards leaving the provisional period
+        let veteran = UserProfile { games_played: 40, casual_games: 11, ..UserProfile::default() };
+        assert!(veteran.is_provisional());
+        assert_eq!(veteran.k_factor(), 40);
+
+        // Rated games between the same players still move their ratings
+        create_game(&mut deps, &white, &black, DAILY).unwrap();
+        execute_as(&mut deps, &black, ExecuteMsg::ResignGame { game_id: 2 }).unwrap();
+        assert_eq!(USER_PROFILES.load(&deps.storage, white).unwrap().elo, 1220);
+    }
+
+    fn rating_history(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, player: &Addr, start_after: Option<u32>, order: Option<SortOrder>) -> Vec<(u32, u64, u32, u32)> {
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetRatingHistory { player: player.clone(), start_after, limit: Some(2), order }).unwrap();
+        from_json::<RatingHistoryResponse>(&res).unwrap().entries.into_iter()
+            .map(|change| (change.index, change.game_id, change.old_rating, change.new_rating))
+            .collect()
+    }
+
+    #[test]
+    fn rating_changes_are_kept_in_history() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_players(&mut deps);
+        // Game 2 finishes first, casual game 3 is left out of the history
+        for rated in [true, true, false, true] {
+            start_game(&mut deps, mock_env(), &white, &black, ColorChoice::White, DAILY, None, rated).unwrap();
+        }
+        execute_as(&mut deps, &black, ExecuteMsg::ResignGame { game_id: 2 }).unwrap();
+        execute_as(&mut deps, &black, ExecuteMsg::ResignGame { game_id: 1 }).unwrap();
+        execute_as(&mut deps, &white, ExecuteMsg::ResignGame { game_id: 3 }).unwrap();
+        execute_as(&mut deps, &white, ExecuteMsg::ResignGame { game_id: 4 }).unwrap();
+
+        assert_eq!(rating_history(&deps, &white, None, None), vec![(1, 2, 1200, 1220), (2, 1, 1220, 1238)]);
+        assert_eq!(rating_history(&deps, &white, Some(2), None), vec![(3, 4, 1238, 1214)]);
+        assert_eq!(rating_history(&deps, &white, None, Some(SortOrder::Descending)), vec![(3, 4, 1238, 1214), (2, 1, 1220, 1238)]);
+        assert_eq!(rating_history(&deps, &black, None, None), vec![(1, 2, 1200, 1180), (2, 1, 1180, 1162)]);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetRatingHistory { player: white.clone(), start_after: None, limit: None, order: None }).unwrap();
+        let change = from_json::<RatingHistoryResponse>(&res).unwrap().entries.remove(0);
+        assert_eq!(change.opponent, black);
+        assert_eq!(change.category, RatingCategory::Correspondence);
+        assert_eq!((change.old_category_rating, change.new_category_rating), (1200, 1220));
+        assert_eq!(change.block_height, mock_env().block.height);
+
+        let profile = USER_PROFILES.load(&deps.storage, white).unwrap();
+        assert_eq!((profile.elo, profile.peak_elo), (1214, 1238));
+    }
+
+    #[test]
+    fn move_after_flag_fall_commits_timeout_loss() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+        let start = mock_env().block.height;
+
+        play(&mut deps, &white, 1, "e2e4");
+        play(&mut deps, &black, 1, "e7e5");
+
+        let res = execute(deps.as_mut(), env_at(start + 200_000), message_info(&white, &[]), ExecuteMsg::MakeMove {
+            game_id: 1,
+            from: "g1".to_string(),
+            to: "f3".to_string(),
+            promotion: None,
+        }).unwrap();
+        assert_eq!(attribute(&res, "move_rejected"), "time_expired");
+        assert_eq!(attribute(&res, "termination"), "timeout");
+
+        let game = load_game(&deps, 1);
+        assert_eq!(game.status, GameState::BlackWon { reason: Termination::Timeout });
+        assert_eq!(stored_moves(&deps, 1), "e2e4,e7e5");
+
+        // The flagged player can't simply try again
+        let err = execute(deps.as_mut(), env_at(start + 200_001), message_info(&white, &[]), ExecuteMsg::MakeMove {
+            game_id: 1,
+            from: "g1".to_string(),
+            to: "f3".to_string(),
+            promotion: None,
+        }).unwrap_err();
+        assert!(matches!(err, ContractError::GameNotActive {}));
+    }
+
+    fn shuffle_knights(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, white: &Addr, black: &Addr, game_id: u64) -> Response {
+        play(deps, white, game_id, "g1f3");
+        play(deps, black, game_id, "g8f6");
+        play(deps, white, game_id, "f3g1");
+        play(deps, black, game_id, "f6g8")
+    }
+
+    #[test]
+    fn fivefold_repetition_ends_the_game() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+
+        for _ in 0..3 {
+            shuffle_knights(&mut deps, &white, &black, 1);
+        }
+        assert!(load_game(&deps, 1).status.is_active());
+
+        let res = shuffle_knights(&mut deps, &white, &black, 1);
+        assert_eq!(attribute(&res, "termination"), "fivefold_repetition");
+        assert_eq!(load_game(&deps, 1).status, GameState::Draw { reason: Termination::FivefoldRepetition });
+    }
+
+    #[test]
+    fn seventy_five_move_rule_ends_the_game() {
+        let mut deps = mock_dependencies();
+        let (white, _black) = setup_game(&mut deps);
+
+        let mut game = load_game(&deps, 1);
+        game.current_fen = "8/8/4k3/8/8/3RK3/8/8 w - - 149 100".to_string();
+        GAMES.save(deps.as_mut().storage, 1, &game).unwrap();
+
+        let res = play(&mut deps, &white, 1, "d3d1");
+        assert_eq!(attribute(&res, "termination"), "seventy_five_move_rule");
+    }
+
+    #[test]
+    fn checkmate_takes_precedence_over_seventy_five_move_rule() {
+        let mut deps = mock_dependencies();
+        let (white, _black) = setup_game(&mut deps);
+
+        let mut game = load_game(&deps, 1);
+        game.current_fen = "4k3/8/4K3/8/8/8/8/R7 w - - 149 100".to_string();
+        GAMES.save(deps.as_mut().storage, 1, &game).unwrap();
+
+        let res = play(&mut deps, &white, 1, "a1a8");
+        assert_eq!(attribute(&res, "termination"), "checkmate");
+    }
+
+    #[test]
+    fn time_control_must_respect_bounds() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_game(&mut deps);
+
+        let err = create_game(&mut deps, &white, &black, TimeControl::Fischer { initial: 10, increment: 0 }).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTimeControl { .. }));
+        let err = create_game(&mut deps, &white, &black, TimeControl::Bronstein { initial: 600, delay: 100_000 }).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTimeControl { .. }));
+        let err = create_game(&mut deps, &white, &black, TimeControl::Correspondence { per_move: 60 }).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTimeControl { .. }));
+
+        // Admin can disable unlimited games
+        let admin = deps.api.addr_make("admin");
+        let bounds = TimeControlBounds { allow_unlimited: false, ..TimeControlBounds::default() };
+        execute_as(&mut deps, &admin, ExecuteMsg::UpdateConfig { admin: None, time_control_bounds: Some(bounds), avg_block_time_ms: None, rating_system: None, house_fee_bps: None, treasury: None, clear_treasury: false, cw20_allowlist: None, nft_contract: None }).unwrap();
+        let err = create_game(&mut deps, &white, &black, TimeControl::Unlimited).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTimeControl { .. }));
+    }
+
+    fn time_status(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, height: u64) -> TimeStatusResponse {
+        let res = query(deps.as_ref(), env_at(height), QueryMsg::CheckTimeStatus { game_id: 1 }).unwrap();
+        from_json(&res).unwrap()
+    }
+
+    fn play_at(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, player: &Addr, height: u64, uci: &str) {
+        execute(deps.as_mut(), env_at(height), message_info(player, &[]), ExecuteMsg::MakeMove {
+            game_id: 1,
+            from: uci[0..2].to_string(),
+            to: uci[2..4].to_string(),
+            promotion: None,
+        }).unwrap();
+    }
+
+    #[test]
+    fn fischer_increment_is_added_after_each_move() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_players(&mut deps);
+        create_game(&mut deps, &white, &black, TimeControl::Fischer { initial: 600, increment: 10 }).unwrap();
+        let start = mock_env().block.height;
+
+        play_at(&mut deps, &white, start, "e2e4");
+        play_at(&mut deps, &black, start, "e7e5");
+        play_at(&mut deps, &white, start + 100, "g1f3");
+
+        let status = time_status(&deps, start + 150);
+        assert_eq!(status.white_time_remaining, 510);
+        assert_eq!(status.black_time_remaining, 550);
+        assert!(!status.time_expired);
+        assert!(time_status(&deps, start + 700).time_expired);
+    }
+
+    #[test]
+    fn bronstein_delay_refunds_time_up_to_the_delay() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_players(&mut deps);
+        create_game(&mut deps, &white, &black, TimeControl::Bronstein { initial: 600, delay: 30 }).unwrap();
+        let start = mock_env().block.height;
+
+        play_at(&mut deps, &white, start, "e2e4");
+        play_at(&mut deps, &black, start, "e7e5");
+        // Fast move costs nothing, slow move costs the time above the delay
+        play_at(&mut deps, &white, start + 20, "g1f3");
+        play_at(&mut deps, &black, start + 120, "b8c6");
+
+        let status = time_status(&deps, start + 120);
+        assert_eq!(status.white_time_remaining, 600);
+        assert_eq!(status.black_time_remaining, 530);
+    }
+
+    #[test]
+    fn correspondence_clock_resets_every_move() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_players(&mut deps);
+        create_game(&mut deps, &white, &black, TimeControl::Correspondence { per_move: 86_400 }).unwrap();
+        let start = mock_env().block.height;
+
+        play_at(&mut deps, &white, start, "e2e4");
+        play_at(&mut deps, &black, start, "e7e5");
+        play_at(&mut deps, &white, start + 80_000, "g1f3");
+
+        let status = time_status(&deps, start + 80_000);
+        assert_eq!(status.white_time_remaining, 86_400);
+        assert!(!time_status(&deps, start + 166_399).time_expired);
+        assert!(time_status(&deps, start + 166_400).time_expired);
+    }
+
+    #[test]
+    fn unlimited_games_never_time_out() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_players(&mut deps);
+        create_game(&mut deps, &white, &black, TimeControl::Unlimited).unwrap();
+        let start = mock_env().block.height;
+
+        play_at(&mut deps, &white, start, "e2e4");
+        play_at(&mut deps, &black, start, "e7e5");
+        assert!(!time_status(&deps, start + 10_000_000).time_expired);
+
+        let err = execute(deps.as_mut(), env_at(start + 10_000_000), message_info(&black, &[]), ExecuteMsg::ClaimTimeout {
+            game_id: 1,
+        }).unwrap_err();
+        assert!(matches!(err, ContractError::NoClock {}));
+        play_at(&mut deps, &white, start + 10_000_000, "g1f3");
+    }
+
+    fn env_at_time(height: u64, seconds: u64) -> Env {
+        let mut env = env_at(height);
+        env.block.time = Timestamp::from_seconds(seconds);
+        env
+    }
+
+    #[test]
+    fn seconds_clock_follows_block_time_not_height() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_players(&mut deps);
+        let admin = deps.api.addr_make("admin");
+        execute_as(&mut deps, &admin, ExecuteMsg::UpdateConfig { admin: None, time_control_bounds: None, avg_block_time_ms: Some(6_000), rating_system: None, house_fee_bps: None, treasury: None, clear_treasury: false, cw20_allowlist: None, nft_contract: None }).unwrap();
+        start_game(&mut deps, mock_env(), &white, &black, ColorChoice::White, TimeControl::Fischer { initial: 600, increment: 0 }, Some(ClockUnit::Seconds), true).unwrap();
+        let (height, time) = (mock_env().block.height, mock_env().block.time.seconds());
+
+        for (player, uci) in [(&white, "e2e4"), (&black, "e7e5")] {
+            execute(deps.as_mut(), env_at_time(height, time), message_info(player, &[]), ExecuteMsg::MakeMove {
+                game_id: 1,
+                from: uci[0..2].to_string(),
+                to: uci[2..4].to_string(),
+                promotion: None,
+            }).unwrap();
+        }
+
+        // Slow blocks: 10 blocks but 120 seconds
+        let res = query(deps.as_ref(), env_at_time(height + 10, time + 120), QueryMsg::CheckTimeStatus { game_id: 1 }).unwrap();
+        let status: TimeStatusResponse = from_json(&res).unwrap();
+        assert_eq!(status.clock_unit, ClockUnit::Seconds);
+        assert_eq!(status.white_time_remaining, 480);
+        assert_eq!(status.white_seconds_remaining, 480);
+        assert_eq!(status.white_blocks_remaining, 80);
+        assert_eq!(status.black_blocks_remaining, 100);
+        assert_eq!(status.seconds_since_last_move, 120);
+        assert_eq!(status.blocks_since_last_move, 10);
+        let err = execute(deps.as_mut(), env_at_time(height + 10, time + 120), message_info(&black, &[]), ExecuteMsg::ClaimTimeout { game_id: 1 }).unwrap_err();
+        assert_eq!(err.to_string(), "Opponent still has 480 seconds on the clock");
+
+        // Many blocks in little time do not flag the player
+        let res = query(deps.as_ref(), env_at_time(height + 1_000, time + 599), QueryMsg::CheckTimeStatus { game_id: 1 }).unwrap();
+        assert!(!from_json::<TimeStatusResponse>(&res).unwrap().time_expired);
+        let res = query(deps.as_ref(), env_at_time(height + 1, time + 600), QueryMsg::CheckTimeStatus { game_id: 1 }).unwrap();
+        assert!(from_json::<TimeStatusResponse>(&res).unwrap().time_expired);
+    }
+
+    #[test]
+    fn block_clock_reports_estimated_seconds() {
+        let mut deps = mock_dependencies();
+        let (white, black) = setup_players(&mut deps);
+        create_game(&mut deps, &white, &black, TimeControl::Fischer { initial: 600, increment: 0 }).unwrap();
+
+        let status = time_status(&deps, mock_env().block.height);
+        assert_eq!(status.clock_unit, ClockUnit::Blocks);
+        assert_eq!(status.white_blocks_remaining, 600);
+        assert_eq!(status.white_seconds_remaining, 600);
+    }
+
+    #[test]
+    fn moves_pack_into_16_bits() {
+        for uci in ["e2e4", "e1g1", "a7a8q", "h2g1n", "b7c8r", "g7f8b"] {
+            let parsed: UciMove = uci.parse().unwrap();
+            assert_eq!(unpack_move(pack_move(&parsed).unwrap()).unwrap(), parsed);
+        }
+        // a1 = 0, h8 = 63, queen = 5
+        assert_eq!(pack_move(&"a1h8".parse().unwrap()).unwrap(), 0x003f);
+        assert_eq!(pack_move(&"h7h8q".parse().unwrap()).unwrap(), 0x5dff);
+        // Pawn (1), king (6) and anything above aren't promotion pieces
+        for promotion in [1u16, 6, 7, 15] {
+            assert!(unpack_move(promotion << 12 | 0x0dff).is_err());
+        }
+    }
+
+    /// Storage charging gas like the Cosmos SDK KV store (`KVGasConfig` defaults)
+    #[derive(Default)]
+    struct MeteredStorage {
+        inner: MockStorage,
+        gas: std::cell::Cell<u64>,
+    }
+
+    impl MeteredStorage {
+        fn charge(&self, gas: usize) {
+            self.gas.set(self.gas.get() + gas as u64);
+        }
+    }
+
+    impl cosmwasm_std::Storage for MeteredStorage {
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            let value = self.inner.get(key);
+            self.charge(1_000 + 3 * (key.len() + value.as_ref().map_or(0, Vec::len)));
+            value
+        }
+
+        fn range<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>, order: cosmwasm_std::Order) -> Box<dyn Iterator<Item = cosmwasm_std::Record> + 'a> {
+            Box::new(self.inner.range(start, end, order).inspect(|(key, value)| self.charge(30 + 3 * (key.len() + value.len()))))
+        }
+
+        fn set(&mut self, key: &[u8], value: &[u8]) {
+            self.charge(2_000 + 30 * (key.len() + value.len()));
+            self.inner.set(key, value);
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.charge(1_000);
+            self.inner.remove(key);
+        }
+    }
+
+    /// Benchmark of the storage gas of `MakeMove` over a long game. Run with `--nocapture` for
+    /// the per-ply table. Next to the full move it meters, with the same gas table, what each
+    /// layout spends on keeping the moves: the game record plus the packed chunk now, against
+    /// the game record carrying the comma-separated `moves` string before, which the move read
+    /// once, the indexed save read again and then wrote back. The `MOVE_HISTORY` record with
+    /// the SAN and FEN is written on every ply by both layouts and is listed on its own.
+    #[test]
+    fn move_gas_stays_flat_over_long_games() {
+        let mut deps = OwnedDeps {
+            storage: MeteredStorage::default(),
+            api: MockApi::default(),
+            querier: MockQuerier::default(),
+            custom_query_type: std::marker::PhantomData,
+        };
+        let (white, black) = (deps.api.addr_make("white"), deps.api.addr_make("black"));
+        instantiate(deps.as_mut(), mock_env(), message_info(&white, &[]), InstantiateMsg { admin: None, time_control_bounds: None, avg_block_time_ms: None, rating_system: None, house_fee_bps: None, treasury: None, cw20_allowlist: None, nft_contract: None }).unwrap();
+        execute(deps.as_mut(), mock_env(), message_info(&white, &[]), ExecuteMsg::CreateChallenge {
+            opponent: Some(black.to_string()),
+            color: ColorChoice::White,
+            time_control: TimeControl::Unlimited,
+            clock_unit: None,
+            rated: true,
+            color_commitment: None,
+        }).unwrap();
+        execute(deps.as_mut(), mock_env(), message_info(&black, &[]), ExecuteMsg::AcceptChallenge { challenge_id: 1, entropy: None }).unwrap();
+
+        // Replays of each layout's writes, metered on their own
+        let (mut packed, mut legacy, mut history) = (MeteredStorage::default(), MeteredStorage::default(), MeteredStorage::default());
+        let game_key = GAMES.key(1);
+        let rewrite_game = |storage: &mut MeteredStorage, record: &[u8]| {
+            storage.get(&game_key);
+            storage.get(&game_key);
+            storage.set(&game_key, record);
+        };
+        let mut moves = String::new();
+
+        // A deterministic walk through legal moves
+        let mut pos = Chess::default();
+        let (mut gas, mut packed_gas, mut legacy_gas, mut history_gas) = (vec![], vec![], vec![], vec![]);
+        for ply in 0..160usize {
+            let legal = pos.legal_moves();
+            let chess_move = legal[(ply * 7_919) % legal.len()].clone();
+            let uci = UciMove::from_standard(&chess_move);
+            let uci_str = uci.to_string();
+            let player = if ply % 2 == 0 { &white } else { &black };
+
+            deps.storage.gas.set(0);
+            execute(deps.as_mut(), mock_env(), message_info(player, &[]), ExecuteMsg::MakeMove {
+                game_id: 1,
+                from: uci_str[0..2].to_string(),
+                to: uci_str[2..4].to_string(),
+                promotion: uci_str.get(4..5).map(|p| p.to_string()),
+            }).unwrap();
+            pos.play_unchecked(&chess_move);
+            gas.push(deps.storage.gas.get());
+
+            let record = deps.storage.inner.get(&game_key).unwrap();
+            packed.gas.set(0);
+            rewrite_game(&mut packed, &record);
+            push_move(&mut packed, 1, ply as u32, pack_move(&uci).unwrap()).unwrap();
+            packed_gas.push(packed.gas.get());
+
+            if !moves.is_empty() {
+                moves.push(',');
+            }
+            moves.push_str(&uci_str);
+            let with_moves = [&record[..record.len() - 1], format!(",\"moves\":\"{}\"}}", moves).as_bytes()].concat();
+            legacy.gas.set(0);
+            rewrite_game(&mut legacy, &with_moves);
+            legacy_gas.push(legacy.gas.get());
+
+            let history_key = MOVE_HISTORY.key((1, ply as u32 + 1));
+            history.gas.set(0);
+            history.set(&history_key, &deps.storage.inner.get(&history_key).unwrap());
+            history_gas.push(history.gas.get());
+        }
+        assert!(load_game_from(&deps.storage, 1).status.is_active());
+
+        println!("{:>5} {:>10} {:>10} {:>14} {:>14}", "ply", "make_move", "history", "game+packed", "game+string");
+        for ply in (0..160).step_by(10) {
+            println!("{:>5} {:>10} {:>10} {:>14} {:>14}", ply + 1, gas[ply], history_gas[ply], packed_gas[ply], legacy_gas[ply]);
+        }
+
+        // Only the position and the last chunk of packed moves change size, so late moves cost
+        // no more than early ones. Medians, as pawn moves and captures also pay for dropping the
+        // repetition counts of the positions before them.
+        let median = |window: &[u64]| {
+            let mut window = window.to_vec();
+            window.sort_unstable();
+            window[window.len() / 2]
+        };
+        let (early, late) = (median(&gas[20..40]), median(&gas[140..160]));
+        assert!(late <= early + 500, "gas grew from {} to {}", early, late);
+
+        // The string layout pays for every earlier move again on each ply
+        let (packed_late, legacy_late) = (median(&packed_gas[140..160]), median(&legacy_gas[140..160]));
+        assert!(packed_late < legacy_late, "packed moves cost {} against {} for the string", packed_late, legacy_late);
+        assert!(legacy_late > median(&legacy_gas[20..40]) + 10_000);
+    }
+
+    fn load_game_from(storage: &MeteredStorage, game_id: u64) -> ChessGame {
+        GAMES.load(&storage.inner, game_id).unwrap()
+    }
+}